@@ -4,6 +4,8 @@ use std::any::Any;
 pub struct Machine<A, D> {
     state: Box<dyn ErasedState<Action = A, Data = D>>,
     stack: Vec<Box<dyn ErasedState<Action = A, Data = D>>>,
+    /// Stack lengths at which states were pushed with `push_detached`.
+    detached: Vec<usize>,
 }
 
 enum PrivilegedActResult<A, D> {
@@ -168,9 +170,25 @@ impl<A, D> Machine<A, D> {
         Self {
             state: Box::new(state) as _,
             stack: Vec::new(),
+            detached: Vec::new(),
         }
     }
 
+    /// Pushes a state on top of whatever is active, from outside the machine. The state
+    /// below isn't asked, and its `resume` isn't called when the pushed state returns;
+    /// the returned value is dropped instead.
+    pub fn push_detached(
+        &mut self,
+        data: &mut D,
+        state: impl State<Action = A, Data = D> + 'static,
+    ) {
+        let old = std::mem::replace(&mut self.state, Box::new(state) as _);
+        self.stack.push(old);
+        self.detached.push(self.stack.len());
+        let result = self.state.on_enter(data);
+        self.apply_result(data, result, self.stack.len());
+    }
+
     pub fn act(&mut self, data: &mut D, action: A) {
         let result = self.state.act(data, action);
         self.apply_result(data, result, self.stack.len());
@@ -183,6 +201,7 @@ impl<A, D> Machine<A, D> {
                 for mut state in self.stack.drain(..).rev() {
                     state.on_exit(data);
                 }
+                self.detached.clear();
                 self.state = state;
                 let result = self.state.on_enter(data);
                 self.apply_result(data, result, 0);
@@ -205,6 +224,10 @@ impl<A, D> Machine<A, D> {
                 // TODO: Make this forbidden if not on top of stack
                 self.state.on_exit(data);
                 self.state = self.stack.pop().expect("Returned on empty stack");
+                if self.detached.last() == Some(&(self.stack.len() + 1)) {
+                    self.detached.pop();
+                    return;
+                }
                 let result = self.state.resume(data, value);
                 self.apply_result(data, result, stack_pos - 1);
             }
@@ -316,6 +339,38 @@ mod test {
         }
     }
 
+    /// Returns on the first action it gets.
+    struct Interrupt;
+
+    impl State for Interrupt {
+        type Action = Action;
+        type Data = Data;
+        type Input = ();
+        type Return = ();
+
+        fn act(
+            &mut self,
+            _data: &mut Self::Data,
+            _action: Self::Action,
+        ) -> ActResult<Self::Action, Self::Data> {
+            self.pop(())
+        }
+    }
+
+    #[test]
+    fn test_push_detached() {
+        let mut machine = Machine::new(Begin);
+        let mut data = Data { value: 0 };
+
+        // `Begin` would panic if resumed with the () returned by `Interrupt`.
+        machine.push_detached(&mut data, Interrupt);
+        machine.act(&mut data, Action::Begin);
+
+        machine.act(&mut data, Action::Begin);
+        machine.act(&mut data, Action::Set(10));
+        assert_eq!(data.value, 10);
+    }
+
     #[test]
     fn test_machine() {
         let mut machine = Machine::new(Begin);
@@ -0,0 +1,104 @@
+#[cfg(test)]
+use std::cell::Cell;
+#[cfg(test)]
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::UNIX_EPOCH;
+use std::{
+    fmt::Debug,
+    time::{Instant, SystemTime},
+};
+
+/// A source of the current time. Timers read time through this so tests can drive them
+/// without sleeping.
+pub(crate) trait Clock: Debug {
+    fn now(&self) -> Instant;
+    /// The wall-clock time, for anything tied to calendar days.
+    fn wall_now(&self) -> SystemTime;
+    /// Seconds east of UTC of the local time zone at `time`.
+    fn utc_offset(&self, time: SystemTime) -> i64;
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// Asks the C library, which reads the TZ variable and the system time zone.
+    #[cfg(unix)]
+    fn utc_offset(&self, time: SystemTime) -> i64 {
+        let secs = time.duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs());
+        let secs = secs as libc::time_t;
+        // SAFETY: `libc::tm` is a plain C struct of integers and a pointer, for which
+        // all zeroes is a valid value.
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        // SAFETY: both pointers come from references that live for the whole call.
+        // `localtime_r` only writes to `tm`, and unlike `localtime` keeps no shared state.
+        if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff as i64
+    }
+
+    /// Elsewhere there is no offset lookup, and days fall back to UTC days.
+    #[cfg(not(unix))]
+    fn utc_offset(&self, _time: SystemTime) -> i64 {
+        0
+    }
+}
+
+/// A clock that only moves when told to.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct MockClock {
+    instant: Cell<Instant>,
+    wall: Cell<SystemTime>,
+    offset: Cell<i64>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    /// Starts at the current time, in UTC.
+    pub(crate) fn new() -> Self {
+        MockClock {
+            instant: Cell::new(Instant::now()),
+            wall: Cell::new(SystemTime::now()),
+            offset: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn advance(&self, dur: Duration) {
+        self.instant.set(self.instant.get() + dur);
+        self.wall.set(self.wall.get() + dur);
+    }
+
+    pub(crate) fn set_wall(&self, time: SystemTime) {
+        self.wall.set(time);
+    }
+
+    pub(crate) fn set_utc_offset(&self, secs: i64) {
+        self.offset.set(secs);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.instant.get()
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        self.wall.get()
+    }
+
+    fn utc_offset(&self, _time: SystemTime) -> i64 {
+        self.offset.get()
+    }
+}
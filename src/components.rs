@@ -1,33 +1,155 @@
 use std::borrow::Cow;
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
 use unicode_segmentation::UnicodeSegmentation;
 
+use crate::clock::Clock;
+use crate::config::{format_task_id, RowPart};
+use crate::hooks::TimerHook;
 use crate::task::{self, Filter, TaskId};
 use crate::AppData;
 
-fn status_to_span(status: task::Status) -> Span<'static> {
+/// Drops all styling in no-color mode, where text alone has to carry the meaning, and
+/// just the colors when colors are disabled.
+pub(crate) fn styled(data: &AppData, style: Style) -> Style {
+    if data.config.no_color {
+        Style::default()
+    } else if !data.colors {
+        Style {
+            fg: None,
+            bg: None,
+            ..style
+        }
+    } else {
+        style
+    }
+}
+
+/// Describes how long ago something happened, e.g. "3 hours ago".
+fn humanize_ago(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    let (count, unit) = if minutes < 1 {
+        return "just now".to_owned();
+    } else if minutes < 60 {
+        (minutes, "minute")
+    } else if minutes < 60 * 24 {
+        (minutes / 60, "hour")
+    } else {
+        (minutes / (60 * 24), "day")
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Roughly the number of rows `text` takes when word-wrapped to `width` columns.
+/// Counts graphemes, so wide characters are undercounted.
+fn wrapped_height(text: &str, width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    text.lines()
+        .map(|line| {
+            let mut rows = 1;
+            let mut column = 0;
+            for word in line.split_whitespace() {
+                let len = word.graphemes(true).count();
+                if column > 0 && column + 1 + len > width {
+                    rows += 1;
+                    column = 0;
+                }
+                if column > 0 {
+                    column += 1;
+                }
+                column += len;
+                while column > width {
+                    rows += 1;
+                    column -= width;
+                }
+            }
+            rows
+        })
+        .sum()
+}
+
+fn status_to_span(status: task::Status, data: &AppData) -> Span<'static> {
+    if data.config.no_color {
+        return match status {
+            task::Status::Todo => Span::raw("[TODO]"),
+            task::Status::Done => Span::raw("[DONE]"),
+        };
+    }
     match status {
         task::Status::Todo => Span::styled("TODO", Style::default().add_modifier(Modifier::BOLD)),
         task::Status::Done => Span::styled("DONE", Style::default().add_modifier(Modifier::DIM)),
     }
 }
 
+/// A colored block for the label, or its initial when colors are off.
+fn label_to_span(label: task::Label, data: &AppData) -> Span<'static> {
+    if data.config.no_color || !data.colors {
+        return Span::raw(format!("[{}] ", &label.name()[..1]));
+    }
+    let color = match label {
+        task::Label::Red => Color::Red,
+        task::Label::Yellow => Color::Yellow,
+        task::Label::Green => Color::Green,
+        task::Label::Blue => Color::Blue,
+    };
+    Span::styled("█ ", Style::default().fg(color))
+}
+
+/// A list styled with the theme's selection highlight.
+fn themed_list<'a>(items: Vec<ListItem<'a>>, data: &'a AppData) -> List<'a> {
+    let theme = &data.config.theme;
+    let highlight = if data.colors {
+        theme.highlight_style()
+    } else {
+        Style::default().add_modifier(Modifier::REVERSED)
+    };
+    let list = List::new(items).highlight_style(styled(data, highlight));
+    match &theme.highlight_symbol {
+        Some(symbol) => list.highlight_symbol(symbol),
+        None if data.config.no_color => list.highlight_symbol("> "),
+        None => list,
+    }
+}
+
+/// The top-bordered block around a pane, marked when the pane has focus.
+fn pane_block<'a>(data: &AppData, focused: bool) -> Block<'a> {
+    let block = Block::default().borders(Borders::TOP);
+    if !focused {
+        block
+    } else if data.config.no_color || !data.colors {
+        block.border_type(BorderType::Double)
+    } else {
+        block.border_style(Style::default().fg(Color::Cyan))
+    }
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct TaskList {
     pub(crate) tasks: Vec<TaskId>,
     pub(crate) selection: usize,
-    pub(crate) list_state: ListState,
+    /// Index of the first visible row. Kept in sync with `selection` during rendering.
+    pub(crate) offset: usize,
     pub(crate) title: Option<String>,
+    /// Shown in place of the rows when the list is empty. When unset, the text tells
+    /// apart an empty store from a filter that matches nothing.
+    pub(crate) empty_text: Option<String>,
+    /// The filter the tasks were last chosen with.
+    filter: Option<Filter>,
+    /// Text of an in-list find. Titles containing it are underlined.
+    pub(crate) find: Option<String>,
 }
 
 impl TaskList {
@@ -36,44 +158,206 @@ impl TaskList {
         self
     }
 
+    pub(crate) fn empty_text(mut self, text: impl Into<String>) -> Self {
+        self.empty_text = Some(text.into());
+        self
+    }
+
     pub(crate) fn selection(&self) -> Option<TaskId> {
         self.tasks.get(self.selection).copied()
     }
 
+    /// Done and total task counts over the list. `None` when it's empty.
+    pub(crate) fn progress(&self, data: &AppData) -> Option<(usize, usize)> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+        let done = self
+            .tasks
+            .iter()
+            .filter(|id| data.store.get_task(**id).status == task::Status::Done)
+            .count();
+        Some((done, self.tasks.len()))
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        self.selection = self.selection.saturating_sub(1);
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        if self.selection + 1 < self.tasks.len() {
+            self.selection += 1;
+        }
+    }
+
+    /// Keeps the selection on an existing row, or on 0 when there are none.
+    pub(crate) fn clamp_selection(&mut self) {
+        self.selection = self.selection.min(self.tasks.len().saturating_sub(1));
+    }
+
+    /// Selects the nearest row whose title contains `query`, ignoring case, searching
+    /// `backward` or forward from the selection and wrapping around. With `skip_current`
+    /// the selected row itself is checked last. Returns whether anything matched.
+    pub(crate) fn find(
+        &mut self,
+        data: &AppData,
+        query: &str,
+        backward: bool,
+        skip_current: bool,
+    ) -> bool {
+        let len = self.tasks.len();
+        let query = query.to_lowercase();
+        let start = if skip_current { 1 } else { 0 };
+        for step in start..start + len {
+            let index = if backward {
+                (self.selection + 2 * len - step) % len
+            } else {
+                (self.selection + step) % len
+            };
+            let title = &data.store.get_task(self.tasks[index]).title;
+            if title.to_lowercase().contains(&query) {
+                self.selection = index;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Replaces the rows and preselects the first one.
+    fn set_tasks(&mut self, tasks: Vec<TaskId>) {
+        self.tasks = tasks;
+        self.selection = 0;
+        self.offset = 0;
+    }
+
     pub(crate) fn apply_filter(&mut self, data: &AppData, filter: &Filter) {
+        self.set_tasks(filter.apply(&data.store));
+        self.filter = Some(filter.clone());
+    }
+
+    /// Re-applies a filter after tasks have changed. Keeps the selected task if it still
+    /// matches, and otherwise keeps the selection at the nearest remaining row.
+    pub(crate) fn refresh(&mut self, data: &AppData, filter: &Filter) {
+        let selected = self.selection();
         self.tasks = filter.apply(&data.store);
-        self.selection = 0;
-        self.list_state = Default::default();
+        self.filter = Some(filter.clone());
+        if let Some(pos) = selected.and_then(|id| self.tasks.iter().position(|x| *x == id)) {
+            self.selection = pos;
+        }
+        self.clamp_selection();
     }
 
-    pub(crate) fn show<'a>(
+    /// Applies a filter as a search, with the best matches first.
+    pub(crate) fn apply_search(&mut self, data: &AppData, filter: &Filter) {
+        self.set_tasks(filter.apply_ranked(&data.store));
+        self.filter = Some(filter.clone());
+    }
+
+    /// Scrolls the window of `height` rows so that the selection stays visible.
+    fn scroll_to_selection(&mut self, height: usize) {
+        if self.selection < self.offset {
+            self.offset = self.selection;
+        } else if height > 0 && self.selection >= self.offset + height {
+            self.offset = self.selection + 1 - height;
+        }
+        self.offset = self.offset.min(self.tasks.len().saturating_sub(height));
+    }
+
+    pub(crate) fn show(
         &mut self,
-        data: &'a AppData,
+        data: &AppData,
         frame: &mut Frame<impl Backend>,
         size: Rect,
+        focused: bool,
     ) {
-        // ui::rectangle(stdout, 0, 0, 80, 20)?;
-        let mut items = vec![];
-        for id in &self.tasks {
-            let mut spans = vec![];
-            let task = data.store.get_task(*id);
-            spans.push(status_to_span(task.status));
-            spans.push(Span::raw(" "));
-            spans.push(Span::raw(&task.title));
-            items.push(ListItem::new(vec![Spans::from(spans)]));
-        }
-        let block = Block::default()
-            .borders(Borders::TOP)
-            .title(format!(" {} ", self.title.as_deref().unwrap_or("Tasks")));
+        let title = match &self.title {
+            Some(title) => title.clone(),
+            None => match self.progress(data) {
+                Some((done, total)) if self.filter.as_ref().is_some_and(Filter::is_active) => {
+                    format!(
+                        "Tasks — {}% complete ({}/{})",
+                        done * 100 / total,
+                        done,
+                        total
+                    )
+                }
+                _ => "Tasks".to_owned(),
+            },
+        };
+        let block = pane_block(data, focused).title(format!(" {} ", title));
         let inner = block.inner(size);
         frame.render_widget(block, size);
         let chunks = Layout::default()
             .horizontal_margin(1)
             .constraints([Constraint::Min(0)])
             .split(inner);
-        let list = List::new(items).highlight_style(Style::default().bg(Color::DarkGray));
-        self.list_state.select(Some(self.selection));
-        frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+
+        self.clamp_selection();
+        if self.tasks.is_empty() {
+            let text = match &self.empty_text {
+                Some(text) => text.as_str(),
+                None if data.store.is_empty() => "No tasks — press 'n' to create one",
+                None => "No matches for current filter — press 'f' to adjust",
+            };
+            let paragraph = Paragraph::new(text)
+                .style(styled(data, Style::default().add_modifier(Modifier::DIM)));
+            frame.render_widget(paragraph, chunks[0]);
+            return;
+        }
+
+        // Only the visible window of rows is looked up and built.
+        let height = chunks[0].height as usize;
+        self.scroll_to_selection(height);
+        let end = (self.offset + height).min(self.tasks.len());
+        let mut items = vec![];
+        for id in &self.tasks[self.offset..end] {
+            let task = data.store.get_task(*id);
+            let mut spans: Vec<_> = data
+                .config
+                .row_format
+                .0
+                .iter()
+                .map(|part| match part {
+                    RowPart::Literal(text) => Span::raw(text.as_str()),
+                    RowPart::Status => status_to_span(task.status, data),
+                    RowPart::Title => match &self.find {
+                        Some(find)
+                            if !find.is_empty()
+                                && task.title.to_lowercase().contains(&find.to_lowercase()) =>
+                        {
+                            Span::styled(
+                                task.title.as_str(),
+                                styled(data, Style::default().add_modifier(Modifier::UNDERLINED)),
+                            )
+                        }
+                        _ => Span::raw(task.title.as_str()),
+                    },
+                    RowPart::Id => Span::raw(format_task_id(task.id, &data.config)),
+                    RowPart::Pomodoros => Span::raw("#".repeat(task.pomodoros.max(0) as usize)),
+                })
+                .collect();
+            if let Some(label) = task.label {
+                spans.insert(0, label_to_span(label, data));
+            }
+            if data.running_task == Some(*id) {
+                spans.push(Span::styled(
+                    " ●",
+                    styled(
+                        data,
+                        Style::default()
+                            .fg(Color::Red)
+                            .add_modifier(Modifier::SLOW_BLINK),
+                    ),
+                ));
+            }
+            items.push(ListItem::new(vec![Spans::from(spans)]));
+        }
+        let list = themed_list(items, data);
+        let mut list_state = ListState::default();
+        if self.selection < end {
+            list_state.select(Some(self.selection - self.offset));
+        }
+        frame.render_stateful_widget(list, chunks[0], &mut list_state);
     }
 }
 
@@ -82,29 +366,97 @@ pub(crate) struct TaskView {
     pub(crate) task_id: TaskId,
     pub(crate) link_list: TaskList,
     pub(crate) show_full: bool,
+    /// Show the selected link's description under the link list, in full mode.
+    pub(crate) expand_link: bool,
 }
 
 impl TaskView {
     pub(crate) fn new(task_id: TaskId, data: &AppData, show_full: bool) -> Self {
         let task = data.store.get_task(task_id);
-        let mut link_list = TaskList::default().title("Linked tasks");
+        let mut link_list =
+            TaskList::default().empty_text("No linked tasks — press 'l' to add one");
         link_list.tasks = task.links.clone();
         Self {
             task_id,
             link_list,
             show_full,
+            expand_link: false,
         }
     }
 
-    pub(crate) fn show(&mut self, data: &AppData, frame: &mut Frame<impl Backend>, size: Rect) {
+    /// Pomodoros, completion time and time spent, each with its own leading space.
+    /// Relative times are measured from `now`.
+    fn details(task: &task::Task, now: SystemTime) -> Vec<Span<'static>> {
+        let mut spans = vec![];
+        for _ in 0..task.pomodoros {
+            spans.push(Span::from(" #"));
+        }
+        if let Some(completed) = task.completed_at {
+            let ago = now.duration_since(completed).unwrap_or_default();
+            spans.push(Span::from(format!("  Completed {}", humanize_ago(ago))));
+        }
+        if let Some(reminder) = task.reminder {
+            let left = reminder.duration_since(now).unwrap_or_default();
+            let minutes = left.as_secs().div_ceil(60);
+            spans.push(Span::from(format!("  Reminder in {} min", minutes)));
+        }
+        let minutes = task.time_spent.as_secs() / 60;
+        if minutes > 0 {
+            spans.push(Span::from(format!(
+                "  Spent {}h {:02}m",
+                minutes / 60,
+                minutes % 60
+            )));
+        }
+        spans
+    }
+
+    /// Renders the task in two unbordered lines: status, title and details, then the
+    /// start of the description. For areas too small for the full layout.
+    pub(crate) fn show_compact(&self, data: &AppData, frame: &mut Frame<impl Backend>, size: Rect) {
+        let task = data.store.get_task(self.task_id);
+        let mut spans = vec![status_to_span(task.status, data), Span::from(" ")];
+        if let Some(label) = task.label {
+            spans.push(label_to_span(label, data));
+        }
+        spans.push(Span::styled(
+            task.title.as_str(),
+            styled(data, Style::default().add_modifier(Modifier::BOLD)),
+        ));
+        spans.extend(Self::details(task, data.clock.wall_now()));
+        let description = task.description.lines().next().unwrap_or_default();
+        let text = vec![
+            Spans::from(spans),
+            Spans::from(Span::styled(
+                description,
+                styled(data, Style::default().add_modifier(Modifier::DIM)),
+            )),
+        ];
+        frame.render_widget(Paragraph::new(text), size);
+    }
+
+    /// Rebuilds the link list from the task's current links, which may have been
+    /// changed elsewhere since the view was built.
+    pub(crate) fn sync_links(&mut self, data: &AppData) {
+        let links = &data.store.get_task(self.task_id).links;
+        if self.link_list.tasks != *links {
+            self.link_list.tasks = links.clone();
+        }
+    }
+
+    pub(crate) fn show(
+        &mut self,
+        data: &AppData,
+        frame: &mut Frame<impl Backend>,
+        size: Rect,
+        focused: bool,
+    ) {
         let task = data.store.get_task(self.task_id);
-        let block = Block::default()
-            .borders(Borders::TOP)
-            .title(Spans::from(vec![
-                Span::from(" "),
-                Span::from(task.title.as_str()),
-                Span::from(" "),
-            ]));
+        let block = pane_block(data, focused).title(Spans::from(vec![
+            Span::from(" "),
+            Span::from(task.title.as_str()),
+            Span::from(" "),
+        ]));
         frame.render_widget(block, size);
 
         let h_constraints = if self.show_full {
@@ -124,29 +476,209 @@ impl TaskView {
             .constraints([Constraint::Length(1), Constraint::Min(1)])
             .split(horizontal[0]);
 
-        let mut spans = vec![Span::from("Status: "), status_to_span(task.status)];
-        for _ in 0..task.pomodoros {
-            spans.push(Span::from(" #"));
-        }
+        let mut spans = vec![Span::from("Status: "), status_to_span(task.status, data)];
+        spans.extend(Self::details(task, data.clock.wall_now()));
 
         let text = vec![Spans::from(spans)];
         let text = Paragraph::new(text);
         frame.render_widget(text, chunks[0]);
 
-        let description = Text::raw(task.description.as_str());
-        let paragraph = Paragraph::new(description).wrap(Wrap { trim: true });
-        frame.render_widget(paragraph, chunks[1]);
+        render_description(data, frame, chunks[1], &task.description, !self.show_full);
 
         if self.show_full {
-            self.link_list.show(data, frame, horizontal[1]);
+            self.sync_links(data);
+            let done = task
+                .links
+                .iter()
+                .filter(|id| data.store.get_task(**id).status == task::Status::Done)
+                .count();
+            self.link_list.title = Some(match task.links.len() {
+                0 => "Linked tasks".to_owned(),
+                total => format!("Linked tasks — {}/{} done", done, total),
+            });
+            let mut links_area = horizontal[1];
+            if let Some(link) = self.link_list.selection().filter(|_| self.expand_link) {
+                let halves = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+                    .split(links_area);
+                links_area = halves[0];
+                let link = data.store.get_task(link);
+                let block = pane_block(data, false).title(format!(" {} ", link.title));
+                let area = block.inner(halves[1]);
+                frame.render_widget(block, halves[1]);
+                render_description(data, frame, area, &link.description, true);
+            }
+            self.link_list.show(data, frame, links_area, focused);
         }
     }
 }
 
+/// Renders a wrapped task description. With `mark_more`, text that doesn't fit ends in
+/// a "… (more)" marker.
+fn render_description(
+    data: &AppData,
+    frame: &mut Frame<impl Backend>,
+    mut area: Rect,
+    text: &str,
+    mark_more: bool,
+) {
+    let overflows = wrapped_height(text, area.width as usize) > area.height as usize;
+    if mark_more && overflows && area.height > 1 {
+        area.height -= 1;
+        let more = Rect {
+            y: area.y + area.height,
+            height: 1,
+            ..area
+        };
+        let style = styled(data, Style::default().add_modifier(Modifier::DIM));
+        frame.render_widget(Paragraph::new(Span::styled("… (more)", style)), more);
+    }
+
+    let paragraph = Paragraph::new(Text::raw(text)).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+/// Tasks in one column per status, each column filtered like the main list.
+#[derive(Debug)]
+pub(crate) struct BoardView {
+    pub(crate) columns: Vec<(task::Status, TaskList)>,
+    /// Index of the focused column.
+    pub(crate) column: usize,
+}
+
+impl BoardView {
+    pub(crate) fn new(data: &AppData, filter: &Filter) -> Self {
+        let columns = [(task::Status::Todo, "Todo"), (task::Status::Done, "Done")]
+            .iter()
+            .map(|(status, title)| {
+                (
+                    *status,
+                    TaskList::default().title(*title).empty_text("Empty"),
+                )
+            })
+            .collect();
+        let mut view = Self { columns, column: 0 };
+        view.refresh(data, filter);
+        view
+    }
+
+    /// Re-sorts tasks into their columns. Each column keeps its selected task if it's
+    /// still there.
+    pub(crate) fn refresh(&mut self, data: &AppData, filter: &Filter) {
+        for (status, list) in &mut self.columns {
+            let filter = Filter {
+                status: Some(*status),
+                ..filter.clone()
+            };
+            list.refresh(data, &filter);
+        }
+    }
+
+    /// Moves the selected task `offset` columns over by changing its status, and
+    /// follows it there.
+    pub(crate) fn move_selection(&mut self, data: &mut AppData, filter: &Filter, offset: isize) {
+        let id = match self.selection() {
+            Some(id) => id,
+            None => return,
+        };
+        let target = self.column as isize + offset;
+        if target < 0 || target as usize >= self.columns.len() {
+            return;
+        }
+        self.column = target as usize;
+        let now = data.clock.wall_now();
+        data.store
+            .get_task_mut(id)
+            .set_status(self.columns[self.column].0, now);
+        self.refresh(data, filter);
+        let list = self.list();
+        if let Some(pos) = list.tasks.iter().position(|x| *x == id) {
+            list.selection = pos;
+        }
+    }
+
+    pub(crate) fn list(&mut self) -> &mut TaskList {
+        &mut self.columns[self.column].1
+    }
+
+    pub(crate) fn selection(&self) -> Option<TaskId> {
+        self.columns[self.column].1.selection()
+    }
+
+    pub(crate) fn show(
+        &mut self,
+        data: &AppData,
+        frame: &mut Frame<impl Backend>,
+        size: Rect,
+        focused: bool,
+    ) {
+        let count = self.columns.len() as u32;
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, count); count as usize])
+            .split(size);
+        for (i, (_, list)) in self.columns.iter_mut().enumerate() {
+            list.show(data, frame, chunks[i], focused && i == self.column);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TrashView {
+    pub(crate) selection: usize,
+    pub(crate) list_state: ListState,
+}
+
+impl TrashView {
+    pub(crate) fn show(
+        &mut self,
+        data: &AppData,
+        frame: &mut Frame<impl Backend>,
+        size: Rect,
+        focused: bool,
+    ) {
+        let block = pane_block(data, focused).title(" Trash — [r] restore [x] delete [c] empty ");
+        let inner = block.inner(size);
+        frame.render_widget(block, size);
+        let chunks = Layout::default()
+            .horizontal_margin(1)
+            .constraints([Constraint::Min(0)])
+            .split(inner);
+
+        let trash = data.store.trash();
+        if trash.is_empty() {
+            let paragraph = Paragraph::new("Trash is empty")
+                .style(styled(data, Style::default().add_modifier(Modifier::DIM)));
+            frame.render_widget(paragraph, chunks[0]);
+            return;
+        }
+
+        let items: Vec<_> = trash
+            .iter()
+            .map(|task| {
+                ListItem::new(Spans::from(vec![
+                    status_to_span(task.status, data),
+                    Span::raw(" "),
+                    Span::raw(task.title.as_str()),
+                ]))
+            })
+            .collect();
+        let list = themed_list(items, data);
+        self.selection = self.selection.min(trash.len() - 1);
+        self.list_state.select(Some(self.selection));
+        frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+    }
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct QuickInput {
     pub(crate) title: String,
     pub(crate) text: String,
+    /// What the text was understood as, or why it's invalid. Shown after the text.
+    pub(crate) feedback: Option<Result<String, String>>,
+    /// Shown as a "used/max" counter when set.
+    pub(crate) max_len: Option<usize>,
 }
 
 impl QuickInput {
@@ -154,6 +686,8 @@ impl QuickInput {
         Self {
             title: title.into(),
             text: String::new(),
+            feedback: None,
+            max_len: None,
         }
     }
 
@@ -162,12 +696,36 @@ impl QuickInput {
         self
     }
 
-    pub(crate) fn show(&self, _data: &AppData) -> (Paragraph, u16) {
-        let text = Paragraph::new(vec![Spans::from(vec![
+    pub(crate) fn show(&self, data: &AppData) -> (Paragraph<'_>, u16) {
+        let mut spans = vec![
             Span::from(self.title.as_str()),
             Span::from(": "),
             Span::from(self.text.as_str()),
-        ])]);
+        ];
+        match &self.feedback {
+            Some(Ok(meaning)) => spans.push(Span::styled(
+                format!("  → {}", meaning),
+                styled(data, Style::default().add_modifier(Modifier::DIM)),
+            )),
+            Some(Err(error)) => spans.push(Span::styled(
+                format!("  ✗ {}", error),
+                styled(data, Style::default().fg(Color::Red)),
+            )),
+            None => {}
+        }
+        if let Some(max) = self.max_len {
+            let len = self.text.graphemes(true).count();
+            let style = if len >= max {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::DIM)
+            };
+            spans.push(Span::styled(
+                format!("  {}/{}", len, max),
+                styled(data, style),
+            ));
+        }
+        let text = Paragraph::new(vec![Spans::from(spans)]);
         (
             text,
             self.text.graphemes(true).count() as u16 + self.title.len() as u16 + 2,
@@ -175,10 +733,22 @@ impl QuickInput {
     }
 }
 
+/// Shown after the choices of a `QuickSelect`.
+const SELECT_HINT: &str = "(Enter select, Esc cancel)";
+
 #[derive(Debug, Default)]
 pub(crate) struct QuickSelect {
     pub(crate) title: String,
     pub(crate) choices: Vec<(char, String)>,
+    /// Shown after the choice with the same index in the vertical layout.
+    pub(crate) descriptions: Vec<String>,
+    /// Index of the choice picked with Enter.
+    pub(crate) selection: usize,
+    /// Lay out the choices one per row, even if they'd fit on one line.
+    pub(crate) vertical: bool,
+    /// Text typed to narrow the choices, when they're picked by typing rather than by
+    /// hotkey. Filtering always uses the vertical layout.
+    pub(crate) query: Option<String>,
 }
 
 impl QuickSelect {
@@ -186,6 +756,10 @@ impl QuickSelect {
         Self {
             title: title.into(),
             choices: Vec::new(),
+            descriptions: Vec::new(),
+            selection: 0,
+            vertical: false,
+            query: None,
         }
     }
 
@@ -198,19 +772,99 @@ impl QuickSelect {
         self
     }
 
-    pub(crate) fn show(&self, _data: &AppData) -> Paragraph {
+    /// Whether the choices are shown one per row, given the width of the bottom line.
+    pub(crate) fn is_vertical(&self, width: u16) -> bool {
+        let choices: usize = self
+            .choices
+            .iter()
+            .map(|(_, text)| text.chars().count() + 5)
+            .sum();
+        self.vertical
+            || self.query.is_some()
+            || self.title.chars().count() + 2 + choices + SELECT_HINT.len() > width as usize
+    }
+
+    /// Reminds of the keys that work besides the hotkeys.
+    pub(crate) fn hint(&self, data: &AppData) -> Span<'static> {
+        let style = styled(data, Style::default().add_modifier(Modifier::DIM));
+        Span::styled(SELECT_HINT, style)
+    }
+
+    /// Rows taken by the vertical layout, borders included.
+    pub(crate) fn vertical_height(&self) -> u16 {
+        self.choices.len() as u16 + 2
+    }
+
+    pub(crate) fn show(&self, data: &AppData) -> Paragraph<'_> {
         let mut spans = vec![Span::from(self.title.as_str()), Span::from(": ")];
-        for (key, text) in &self.choices {
-            spans.push(Span::raw(format!("[{}] {} ", key, text)));
+        for (i, (key, text)) in self.choices.iter().enumerate() {
+            let text = format!("[{}] {}", key, text);
+            if i == self.selection {
+                let style = Style::default().add_modifier(Modifier::REVERSED);
+                spans.push(Span::styled(text, styled(data, style)));
+            } else {
+                spans.push(Span::raw(text));
+            }
+            spans.push(Span::raw(" "));
         }
+        spans.push(self.hint(data));
         Paragraph::new(vec![Spans::from(spans)])
     }
+
+    /// Renders the choices one per row in a bordered box at the bottom of `area`,
+    /// covering what's there.
+    pub(crate) fn show_vertical(
+        &self,
+        data: &AppData,
+        frame: &mut Frame<impl Backend>,
+        area: Rect,
+    ) {
+        let height = self.vertical_height().min(area.height);
+        let area = Rect {
+            y: area.y + area.height - height,
+            height,
+            ..area
+        };
+        let title = match &self.query {
+            Some(query) => format!(" {}: {}▏", self.title, query),
+            None => format!(" {} ", self.title),
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let dim = styled(data, Style::default().add_modifier(Modifier::DIM));
+        let items = self
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(i, (key, text))| {
+                let mut spans = match self.query {
+                    Some(_) => vec![Span::raw(text.as_str())],
+                    None => vec![Span::raw(format!("[{}] {}", key, text))],
+                };
+                if let Some(description) = self.descriptions.get(i) {
+                    spans.push(Span::styled(format!("  {}", description), dim));
+                }
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+        let list = themed_list(items, data).block(block);
+        let mut state = ListState::default();
+        state.select(Some(self.selection));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
 }
 
 pub(crate) struct Timer {
     pub(crate) title: Cow<'static, str>,
+    pub(crate) started: Instant,
     pub(crate) target: Instant,
+    /// The task this timer is focused on, if any.
+    pub(crate) task: Option<TaskId>,
+    /// Played when the timer completes. `None` plays the default bell.
+    pub(crate) sound: Option<PathBuf>,
     pub(crate) on_done: Box<dyn Fn(&mut AppData)>,
+    /// Run on completion. The name is used when reporting failures.
+    pub(crate) hook: Option<(String, TimerHook)>,
     pub(crate) triggered: bool,
 }
 
@@ -218,33 +872,80 @@ impl Timer {
     pub(crate) fn trigger_in(
         title: impl Into<Cow<'static, str>>,
         dur: Duration,
+        clock: &dyn Clock,
         on_done: impl Fn(&mut AppData) + 'static,
     ) -> Self {
-        let target = Instant::now() + dur;
+        let started = clock.now();
         Self {
             title: title.into(),
-            target,
+            started,
+            target: started + dur,
+            task: None,
+            sound: None,
             on_done: Box::new(on_done) as _,
+            hook: None,
             triggered: false,
         }
     }
 
-    pub(crate) fn is_done(&self) -> bool {
-        let now = Instant::now();
-        self.target < now
+    pub(crate) fn task(mut self, id: TaskId) -> Self {
+        self.task = Some(id);
+        self
+    }
+
+    pub(crate) fn sound(mut self, sound: Option<PathBuf>) -> Self {
+        self.sound = sound;
+        self
     }
 
-    pub(crate) fn show(&self, _data: &AppData) -> Paragraph {
-        let now = Instant::now();
+    pub(crate) fn hook(mut self, name: impl Into<String>, hook: TimerHook) -> Self {
+        self.hook = Some((name.into(), hook));
+        self
+    }
+
+    /// Starts the timer over from the current time, with the same duration.
+    pub(crate) fn restart(&mut self, clock: &dyn Clock) {
+        let duration = self.target - self.started;
+        self.started = clock.now();
+        self.target = self.started + duration;
+    }
+
+    /// Time left until the timer fires at `now`, or `None` once it has fired.
+    ///
+    /// Everything is measured on the monotonic `Instant` clock, so wall clock
+    /// adjustments (NTP, changing the system time) never move the deadline. On Linux
+    /// the monotonic clock stops while the machine is suspended, so a timer paused by
+    /// a suspend resumes with the time it had left.
+    pub(crate) fn remaining(&self, now: Instant) -> Option<Duration> {
         if self.target < now {
-            let dur = now - self.target;
-            return if dur.as_secs() % 2 == 0 {
-                Paragraph::new(vec![Spans::from(format!("{} | DONE ", &self.title))])
-            } else {
-                Paragraph::new(vec![Spans::from(format!("{} |     ", &self.title))])
-            };
+            None
+        } else {
+            Some(self.target - now)
         }
-        let dur = self.target - now;
+    }
+
+    /// Time the timer has run at `now`, up to its full duration.
+    pub(crate) fn elapsed(&self, now: Instant) -> Duration {
+        now.min(self.target) - self.started
+    }
+
+    pub(crate) fn is_done(&self, clock: &dyn Clock) -> bool {
+        self.remaining(clock.now()).is_none()
+    }
+
+    pub(crate) fn show(&self, data: &AppData) -> Paragraph<'_> {
+        let now = data.clock.now();
+        let dur = match self.remaining(now) {
+            Some(dur) => dur,
+            None => {
+                let dur = now - self.target;
+                return if dur.as_secs().is_multiple_of(2) {
+                    Paragraph::new(vec![Spans::from(format!("{} | DONE ", &self.title))])
+                } else {
+                    Paragraph::new(vec![Spans::from(format!("{} |     ", &self.title))])
+                };
+            }
+        };
         let time = dur.as_secs();
         let minutes = time / 60;
         let seconds = time % 60;
@@ -255,3 +956,37 @@ impl Timer {
         Paragraph::new(vec![Spans::from(spans)])
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_humanize_ago() {
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+        assert_eq!(humanize_ago(Duration::from_secs(30)), "just now");
+        assert_eq!(humanize_ago(minutes(1)), "1 minute ago");
+        assert_eq!(humanize_ago(minutes(59)), "59 minutes ago");
+        assert_eq!(humanize_ago(minutes(60 * 5 + 10)), "5 hours ago");
+        assert_eq!(humanize_ago(minutes(60 * 24 * 2)), "2 days ago");
+    }
+
+    #[test]
+    fn test_timer_remaining() {
+        let clock = MockClock::new();
+        let timer = Timer::trigger_in("TEST", Duration::from_secs(60), &clock, |_| {});
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(timer.remaining(clock.now()), Some(Duration::from_secs(40)));
+        assert!(!timer.is_done(&clock));
+
+        clock.advance(Duration::from_secs(40));
+        assert_eq!(timer.remaining(clock.now()), Some(Duration::from_secs(0)));
+        assert!(!timer.is_done(&clock));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(timer.remaining(clock.now()), None);
+        assert!(timer.is_done(&clock));
+    }
+}
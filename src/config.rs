@@ -0,0 +1,221 @@
+use std::{path::PathBuf, time::Duration};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::style::{Color, Modifier, Style};
+
+use crate::{hooks::TimerHook, task::TaskId};
+
+/// User preferences. There is no config file yet, so these are always the defaults.
+#[derive(Debug)]
+pub(crate) struct Config {
+    pub(crate) theme: Theme,
+    /// Accessibility mode: no colors or text attributes at all. Status and focus are
+    /// shown with text markers and border shapes instead.
+    pub(crate) no_color: bool,
+    pub(crate) row_format: RowFormat,
+    /// How long a finished timer keeps showing DONE before it is cleared.
+    /// Zero keeps it until cleared by hand.
+    pub(crate) timer_clear_after: Duration,
+    /// Task count past which a one-time warning is shown. Zero disables it.
+    pub(crate) task_count_warning: usize,
+    /// Played when a timer without its own sound completes.
+    pub(crate) bell: PathBuf,
+    pub(crate) work_sound: Option<PathBuf>,
+    pub(crate) break_sound: Option<PathBuf>,
+    pub(crate) reminder_sound: Option<PathBuf>,
+    /// Cuts completion sounds to this length, fading them out over it.
+    /// Zero plays sounds in full.
+    pub(crate) sound_fade_out: Duration,
+    /// Height of the selected task's preview under the main list, borders included.
+    /// Shrunk when needed so the list keeps a few rows.
+    pub(crate) preview_rows: u16,
+    /// Ask for confirmation before creating a task whose title is already taken.
+    pub(crate) warn_duplicate_titles: bool,
+    /// Put in front of task ids wherever they're shown, e.g. "T-".
+    pub(crate) id_prefix: String,
+    /// Ids are zero-padded to this many digits.
+    pub(crate) id_width: usize,
+    /// Longest title, in graphemes, accepted when creating a task.
+    pub(crate) title_max_len: usize,
+    /// Credit a work timer's elapsed time to its task when the timer is cleared early.
+    pub(crate) credit_partial_work: bool,
+    /// Quits from anywhere, bypassing the automaton.
+    pub(crate) quit_key: KeyEvent,
+    /// Require pressing the quit key twice within a second.
+    pub(crate) quit_double_press: bool,
+    /// Named actions that can be attached to a work timer, in menu order.
+    pub(crate) timer_hooks: Vec<(String, TimerHook)>,
+    /// Work timers to complete each day, shown as progress in the status line.
+    /// Zero hides it.
+    pub(crate) daily_pomodoro_goal: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            no_color: false,
+            row_format: RowFormat::parse("{status} {title}"),
+            timer_clear_after: Duration::from_secs(0),
+            task_count_warning: 5000,
+            bell: "data/bell.wav".into(),
+            work_sound: None,
+            break_sound: None,
+            reminder_sound: None,
+            sound_fade_out: Duration::from_secs(0),
+            preview_rows: 5,
+            warn_duplicate_titles: false,
+            id_prefix: String::new(),
+            id_width: 0,
+            title_max_len: 120,
+            credit_partial_work: false,
+            quit_key: KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            quit_double_press: false,
+            timer_hooks: Vec::new(),
+            daily_pomodoro_goal: 0,
+        }
+    }
+}
+
+/// Formats a task id for display, e.g. "T-0042" with prefix "T-" and width 4.
+pub(crate) fn format_task_id(id: TaskId, config: &Config) -> String {
+    format!(
+        "{}{:0width$}",
+        config.id_prefix,
+        id.id(),
+        width = config.id_width
+    )
+}
+
+/// Reads an id typed by the user, with or without the configured prefix.
+pub(crate) fn parse_task_id(text: &str, config: &Config) -> Option<u64> {
+    let text = text.trim();
+    text.strip_prefix(config.id_prefix.as_str())
+        .unwrap_or(text)
+        .parse()
+        .ok()
+}
+
+#[derive(Debug)]
+pub(crate) struct Theme {
+    /// Background of the selected list row. `None` leaves the background alone.
+    pub(crate) highlight_bg: Option<Color>,
+    pub(crate) highlight_bold: bool,
+    /// Drawn in front of the selected list row, e.g. "▶ ".
+    pub(crate) highlight_symbol: Option<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            highlight_bg: Some(Color::DarkGray),
+            highlight_bold: false,
+            highlight_symbol: None,
+        }
+    }
+}
+
+impl Theme {
+    pub(crate) fn highlight_style(&self) -> Style {
+        let mut style = Style::default();
+        if let Some(bg) = self.highlight_bg {
+            style = style.bg(bg);
+        }
+        if self.highlight_bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        style
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RowPart {
+    Literal(String),
+    Status,
+    Title,
+    Id,
+    Pomodoros,
+}
+
+/// A task list row template such as `"{status} {title}"`.
+///
+/// Known tokens are `{status}`, `{title}`, `{id}` and `{pomodoros}`. Anything else,
+/// including unknown tokens, is rendered literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RowFormat(pub(crate) Vec<RowPart>);
+
+impl RowFormat {
+    pub(crate) fn parse(template: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let after = &rest[start..];
+            let end = match after.find('}') {
+                Some(end) => end,
+                None => {
+                    rest = after;
+                    break;
+                }
+            };
+            let token = match &after[1..end] {
+                "status" => RowPart::Status,
+                "title" => RowPart::Title,
+                "id" => RowPart::Id,
+                "pomodoros" => RowPart::Pomodoros,
+                _ => {
+                    literal.push_str(&after[..=end]);
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                parts.push(RowPart::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(token);
+            rest = &after[end + 1..];
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(RowPart::Literal(literal));
+        }
+        RowFormat(parts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_task_id_format() {
+        let mut store = crate::task::TaskStore::default();
+        let id = store.new_task().id;
+        let config = Config {
+            id_prefix: "T-".into(),
+            id_width: 4,
+            ..Config::default()
+        };
+        assert_eq!(format_task_id(id, &config), "T-0001");
+        assert_eq!(format_task_id(id, &Config::default()), "1");
+        assert_eq!(parse_task_id(" T-0001 ", &config), Some(1));
+        assert_eq!(parse_task_id("1", &config), Some(1));
+    }
+
+    #[test]
+    fn test_row_format() {
+        let format = RowFormat::parse("{id}: {title} {tags} {status");
+        assert_eq!(
+            format.0,
+            vec![
+                RowPart::Id,
+                RowPart::Literal(": ".into()),
+                RowPart::Title,
+                RowPart::Literal(" {tags} {status".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Actions that can be attached to timers, run when the timer completes.
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Something to do when a timer completes. Picked when starting a work timer, either
+/// by name from [`Config::timer_hooks`](crate::config::Config::timer_hooks) or typed in.
+#[derive(Debug, Clone)]
+pub(crate) enum TimerHook {
+    /// Runs a shell command in the background, with the timer's title in `TASKER_TIMER`
+    /// and its task's title in `TASKER_TASK`. Output is discarded.
+    Command(String),
+    /// Appends a tab-separated line of Unix time, timer title and task title to a file.
+    Log(PathBuf),
+}
+
+impl TimerHook {
+    /// Runs the hook for a timer. Errors are only reported if they happen right away:
+    /// a command that starts but then fails goes unnoticed.
+    pub(crate) fn run(&self, timer: &str, task: &str, now: SystemTime) -> io::Result<()> {
+        match self {
+            TimerHook::Command(command) => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .env("TASKER_TIMER", timer)
+                    .env("TASKER_TASK", task)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+                // Reap it off the UI thread.
+                thread::spawn(move || child.wait());
+                Ok(())
+            }
+            TimerHook::Log(path) => {
+                let secs = now.duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs());
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}\t{}\t{}", secs, timer, task)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn test_log_hook() {
+        let path = std::env::temp_dir().join(format!("tasker-hook-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let hook = TimerHook::Log(path.clone());
+        let now = UNIX_EPOCH + Duration::from_secs(100);
+        hook.run("WORK", "Write docs", now).unwrap();
+        hook.run("WORK", "", now).unwrap();
+
+        let log = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(log, "100\tWORK\tWrite docs\n100\tWORK\t\n");
+
+        let hook = TimerHook::Log(path.join("missing"));
+        assert!(hook.run("WORK", "", now).is_err());
+    }
+}
@@ -1,40 +1,124 @@
 mod automaton;
+mod clock;
 mod components;
+mod config;
+mod hooks;
+mod recorder;
 mod state;
 mod task;
+#[cfg(test)]
+mod tests;
 
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     fs::File,
     io::{stdout, BufReader},
-    time::Duration,
+    path::Path,
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
 };
 
 use crossterm::{
     cursor,
-    event::{poll, read, Event, KeyCode, KeyEvent},
+    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{self, disable_raw_mode, enable_raw_mode},
     Result as CResult,
 };
 
 use rodio::Sink;
+#[cfg(test)]
+use tui::{backend::TestBackend, buffer::Buffer};
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
-    Terminal,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Paragraph, Sparkline},
+    Frame, Terminal,
 };
 
-use rodio::{Decoder, OutputStream};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
 
 use automaton::Machine;
+use clock::{Clock, SystemClock};
 use components::*;
-use state::*;
-use task::{Filter, TaskStore};
+use config::Config;
+use hooks::TimerHook;
+use recorder::{MacroEvent, MacroRecorder};
+use state::{dialogs::CaptureState, *};
+use task::{Filter, TaskId, TaskStore};
 
-#[derive(Debug, Default)]
+/// Rows the main list keeps, borders included, however large the preview is set.
+const MIN_LIST_ROWS: u16 = 4;
+
+/// The smallest preview drawn with the full task view: border, status and description.
+/// Smaller previews use the compact view.
+const FULL_PREVIEW_ROWS: u16 = 3;
+
+/// Sparkline counts, keyed by the store revision and local day they were made for.
+type CachedCompletions = ((u64, i64), Vec<u64>);
+
+#[derive(Debug)]
 pub(crate) struct AppData {
     pub(crate) store: TaskStore,
+    pub(crate) config: Config,
+    pub(crate) clock: Rc<dyn Clock>,
+    /// The task of the running timer, mirrored from `Tasker::timer` for rendering.
+    pub(crate) running_task: Option<TaskId>,
+    /// Whether rendering may use colors. Text attributes are used either way.
+    pub(crate) colors: bool,
     pub(crate) window_size: (u16, u16),
+    /// When work timers completed, oldest first.
+    pub(crate) pomodoro_log: Vec<SystemTime>,
+    completions: RefCell<Option<CachedCompletions>>,
+}
+
+impl AppData {
+    /// The local calendar day `time` falls on. See `task::day_of`.
+    fn local_day(&self, time: SystemTime) -> i64 {
+        task::day_of(time, self.clock.utc_offset(time))
+    }
+
+    /// Work timers completed on the same local calendar day as `now`.
+    pub(crate) fn pomodoros_today(&self, now: SystemTime) -> usize {
+        let today = self.local_day(now);
+        let recent = self.pomodoro_log.iter().rev();
+        recent
+            .take_while(|time| self.local_day(**time) == today)
+            .count()
+    }
+
+    /// Tasks completed on each of the last `SPARKLINE_DAYS` local days up to `now`.
+    /// Only counted again once the store or the day changes.
+    pub(crate) fn completions_per_day(&self, now: SystemTime) -> Vec<u64> {
+        let key = (self.store.revision(), self.local_day(now));
+        let mut cache = self.completions.borrow_mut();
+        match &*cache {
+            Some((cached, counts)) if *cached == key => counts.clone(),
+            _ => {
+                let day = |time| self.local_day(time);
+                let counts = self.store.completions_per_day(now, SPARKLINE_DAYS, day);
+                *cache = Some((key, counts.clone()));
+                counts
+            }
+        }
+    }
+}
+
+impl Default for AppData {
+    fn default() -> Self {
+        Self {
+            store: TaskStore::default(),
+            config: Config::default(),
+            clock: Rc::new(SystemClock),
+            running_task: None,
+            colors: true,
+            window_size: (0, 0),
+            pomodoro_log: Vec::new(),
+            completions: RefCell::new(None),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -43,16 +127,13 @@ struct Search {
     list: TaskList,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 enum Pane {
+    #[default]
     Main,
     OneTask(TaskView),
-}
-
-impl Default for Pane {
-    fn default() -> Self {
-        Pane::Main
-    }
+    Trash(TrashView),
+    Board(BoardView),
 }
 
 #[derive(Clone)]
@@ -60,6 +141,13 @@ pub(crate) enum Action {
     Key(KeyEvent),
 }
 
+struct Audio {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    /// The sink of the last sound played. Each sound gets a fresh one.
+    sink: Option<Sink>,
+}
+
 #[derive(Default)]
 pub(crate) struct Tasker {
     pub(crate) tasklist: TaskList,
@@ -68,34 +156,247 @@ pub(crate) struct Tasker {
     pub(crate) search: Option<Search>,
     pub(crate) timer: Option<Timer>,
     pub(crate) pane: Pane,
+    /// The task most recently opened in `OneTaskState`, for flipping back to it.
+    pub(crate) last_task: Option<TaskId>,
     pub(crate) data: AppData,
     pub(crate) filter: Filter,
-    audio: Option<(OutputStream, rodio::OutputStreamHandle, Sink)>,
+    /// Filters saved by name for the current session.
+    pub(crate) saved_filters: HashMap<String, Filter>,
+    /// A one-line message shown in the status line until the next key press.
+    pub(crate) notification: Option<String>,
+    /// Set by states to have the event loop exit.
+    quit_requested: bool,
+    /// When the quit key was last pressed, if double-press quitting is on.
+    quit_pressed: Option<Instant>,
+    /// The task shown alone on screen by `FocusState`.
+    pub(crate) focus: Option<TaskId>,
+    task_count_warned: bool,
+    macros: MacroRecorder,
+    audio: Option<Audio>,
 }
 
 impl Tasker {
+    pub(crate) fn notify(&mut self, text: impl Into<String>) {
+        self.notification = Some(text.into());
+    }
+
+    /// Creates a task. Warns once when the store grows past the configured size.
+    pub(crate) fn create_task(&mut self, title: String) -> TaskId {
+        let task = self.data.store.new_task();
+        task.title = title;
+        let id = task.id;
+
+        let limit = self.data.config.task_count_warning;
+        let count = self.data.store.len();
+        if limit > 0 && count > limit && !self.task_count_warned {
+            self.task_count_warned = true;
+            self.notify(format!(
+                "{} tasks — consider moving finished ones to the trash",
+                count
+            ));
+        }
+        id
+    }
+
+    /// Asks the event loop to exit. This is the only way it exits without an error.
+    fn request_quit(&mut self) {
+        self.quit_requested = true;
+    }
+
+    /// Handles the quit key and 'q'. With double-press quitting, only a second press
+    /// within a second requests the quit.
+    pub(crate) fn press_quit(&mut self) {
+        if !self.data.config.quit_double_press {
+            return self.request_quit();
+        }
+        let now = self.data.clock.now();
+        if let Some(pressed) = self.quit_pressed {
+            if now.duration_since(pressed) <= Duration::from_secs(1) {
+                return self.request_quit();
+            }
+        }
+        self.quit_pressed = Some(now);
+        self.notify("Press again to quit");
+    }
+
+    /// Stops the timer. An unfinished work timer credits its elapsed time to its task if
+    /// configured to.
+    pub(crate) fn clear_timer(&mut self) {
+        let timer = match self.timer.take() {
+            Some(timer) => timer,
+            None => return,
+        };
+        if let Some(id) = timer.task {
+            if self.data.config.credit_partial_work
+                && !timer.triggered
+                && self.data.store.contains(id)
+            {
+                let elapsed = timer.elapsed(self.data.clock.now());
+                self.data.store.get_task_mut(id).time_spent += elapsed;
+            }
+        }
+    }
+
+    /// Moves a task to the trash and drops UI references to it.
+    pub(crate) fn remove_task(&mut self, id: TaskId) {
+        self.data.store.remove_task(id);
+        if self.last_task == Some(id) {
+            self.last_task = None;
+        }
+        self.tasklist.refresh(&self.data, &self.filter);
+    }
+
+    /// Shows a notification for each task whose reminder is due at `now`. Returns
+    /// whether any was.
+    fn fire_reminders(&mut self, now: SystemTime) -> bool {
+        let due = self.data.store.take_due_reminders(now);
+        if due.is_empty() {
+            return false;
+        }
+        // Reminders due together share one notification.
+        let titles: Vec<_> = due
+            .iter()
+            .map(|id| self.data.store.get_task(*id).title.as_str())
+            .collect();
+        let label = if titles.len() == 1 {
+            "Reminder"
+        } else {
+            "Reminders"
+        };
+        self.notify(format!("{}: {}", label, titles.join(", ")));
+        true
+    }
+
     fn update(&mut self) {
+        if self.fire_reminders(self.data.clock.wall_now()) {
+            let sound = self.data.config.reminder_sound.as_ref();
+            let sound = sound.unwrap_or(&self.data.config.bell).clone();
+            self.play_sound(&sound);
+        }
+
+        let mut finished = None;
+        let mut hook = None;
         if let Some(timer) = &mut self.timer {
-            if timer.is_done() && !timer.triggered {
+            if timer.is_done(&*self.data.clock) && !timer.triggered {
                 timer.triggered = true;
+                let now = self.data.clock.wall_now();
+                let before = self.data.pomodoros_today(now);
                 (timer.on_done)(&mut self.data);
-                let (_, _stream_handle, sink) = self.audio.get_or_insert_with(|| {
-                    let (s, h) = OutputStream::try_default().unwrap();
-                    let sink = Sink::try_new(&h).unwrap();
-                    (s, h, sink)
+                let goal = self.data.config.daily_pomodoro_goal;
+                if goal > 0 && before < goal && self.data.pomodoros_today(now) >= goal {
+                    self.notification = Some(format!("Daily goal of {} pomodoros reached!", goal));
+                }
+                finished = Some(match &timer.sound {
+                    Some(sound) => sound.clone(),
+                    None => self.data.config.bell.clone(),
                 });
-                // Load a sound from a file, using a path relative to Cargo.toml
-                let file = BufReader::new(File::open("data/bell.wav").unwrap());
-                // Decode that sound file into a source
-                let source = Decoder::new(file).unwrap();
-                sink.set_volume(0.3);
-                sink.append(source);
+                if let Some(timer_hook) = &timer.hook {
+                    let store = &self.data.store;
+                    let task = timer.task.filter(|id| store.contains(*id));
+                    let task = task.map(|id| store.get_task(id).title.clone());
+                    hook = Some((
+                        timer_hook.clone(),
+                        timer.title.to_string(),
+                        task.unwrap_or_default(),
+                    ));
+                }
             }
         }
+        if let Some(sound) = finished {
+            self.play_sound(&sound);
+        }
+        if let Some(((name, hook), timer, task)) = hook {
+            self.run_timer_hook(&name, &hook, &timer, &task);
+        }
+
+        self.data.running_task = self
+            .timer
+            .as_ref()
+            .filter(|timer| !timer.triggered)
+            .and_then(|timer| timer.task);
+
+        if let Some(timer) = &self.timer {
+            let grace = self.data.config.timer_clear_after;
+            if timer.triggered && !grace.is_zero() && timer.target + grace < self.data.clock.now() {
+                self.timer = None;
+            }
+        }
+    }
+
+    /// Runs a timer hook, reporting failures as a notification.
+    fn run_timer_hook(&mut self, name: &str, hook: &TimerHook, timer: &str, task: &str) {
+        if let Err(err) = hook.run(timer, task, self.data.clock.wall_now()) {
+            self.notify(format!("Timer hook '{}' failed: {}", name, err));
+        }
+    }
+
+    /// Plays a sound file, cutting off any sound still playing. A missing file or audio
+    /// device results in silence.
+    fn play_sound(&mut self, path: &Path) {
+        let fade = self.data.config.sound_fade_out;
+        if self.audio.is_none() {
+            self.audio = OutputStream::try_default()
+                .ok()
+                .map(|(stream, handle)| Audio {
+                    _stream: stream,
+                    handle,
+                    sink: None,
+                });
+        }
+        let audio = match &mut self.audio {
+            Some(audio) => audio,
+            None => return,
+        };
+        let source = match File::open(path)
+            .ok()
+            .and_then(|file| Decoder::new(BufReader::new(file)).ok())
+        {
+            Some(source) => source,
+            None => return,
+        };
+
+        // Dropping the previous sink stops whatever it was still playing.
+        audio.sink = None;
+        let sink = match Sink::try_new(&audio.handle) {
+            Ok(sink) => sink,
+            Err(_) => return,
+        };
+        sink.set_volume(0.3);
+        if fade.is_zero() {
+            sink.append(source);
+        } else {
+            let mut source = source.take_duration(fade);
+            source.set_filter_fadeout();
+            sink.append(source);
+        }
+        audio.sink = Some(sink);
     }
 
     fn show(&mut self, terminal: &mut Terminal<impl Backend>) -> CResult<()> {
         terminal.draw(|f| {
+            if let Some(id) = self.focus {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(2), Constraint::Length(1)])
+                    .split(f.size());
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(1)
+                    .constraints([
+                        Constraint::Percentage(15),
+                        Constraint::Percentage(70),
+                        Constraint::Percentage(15),
+                    ])
+                    .split(chunks[0]);
+                let pane_focused = self.quick_input.is_none() && self.quick_select.is_none();
+                TaskView::new(id, &self.data, false).show(&self.data, f, columns[1], pane_focused);
+                self.show_prompt(f, chunks[0], *chunks.last().unwrap());
+                if let Some(timer) = &self.timer {
+                    render_timer(timer, &self.data, f, chunks[1]);
+                }
+                return;
+            }
+
             let constraints = if let Some(_search) = &mut self.search {
                 vec![
                     Constraint::Min(2),
@@ -110,57 +411,223 @@ impl Tasker {
                 .constraints(constraints)
                 .split(f.size());
 
+            // Dialogs take keyboard focus away from the pane underneath them.
+            let pane_focused =
+                self.quick_input.is_none() && self.quick_select.is_none() && self.search.is_none();
+
             match &mut self.pane {
                 Pane::Main => {
+                    let preview = self
+                        .data
+                        .config
+                        .preview_rows
+                        .min(chunks[0].height.saturating_sub(MIN_LIST_ROWS));
                     let chunks = Layout::default()
                         .direction(Direction::Vertical)
-                        .constraints([Constraint::Min(2), Constraint::Length(5)])
+                        .constraints([Constraint::Min(2), Constraint::Length(preview)])
                         .split(chunks[0]);
-                    self.tasklist.show(&self.data, f, chunks[0]);
+                    self.tasklist.show(&self.data, f, chunks[0], pane_focused);
                     if let Some(id) = self.tasklist.selection() {
-                        TaskView::new(id, &self.data, false).show(&self.data, f, chunks[1]);
+                        let view = TaskView::new(id, &self.data, false);
+                        if preview < FULL_PREVIEW_ROWS {
+                            view.show_compact(&self.data, f, chunks[1]);
+                        } else {
+                            let mut view = view;
+                            view.show(&self.data, f, chunks[1], false);
+                        }
                     }
                 }
                 Pane::OneTask(view) => {
-                    view.show(&self.data, f, chunks[0]);
+                    view.show(&self.data, f, chunks[0], pane_focused);
+                }
+                Pane::Trash(view) => {
+                    view.show(&self.data, f, chunks[0], pane_focused);
+                }
+                Pane::Board(view) => {
+                    view.show(&self.data, f, chunks[0], pane_focused);
                 }
             }
 
-            if let Some(input) = &self.quick_input {
-                let block = *chunks.last().unwrap();
-                let (text, pos) = input.show(&self.data);
-                f.render_widget(text, block);
-                f.set_cursor(block.left() + pos, block.top());
-            }
-
-            if let Some(input) = &self.quick_select {
-                let text = input.show(&self.data);
-                f.render_widget(text, chunks[1]);
-            }
+            self.show_prompt(f, chunks[0], *chunks.last().unwrap());
 
             if let Some(search) = &mut self.search {
-                search.list.show(&self.data, f, chunks[1]);
+                search.list.show(&self.data, f, chunks[1], true);
             }
 
+            let mut line = *chunks.last().unwrap();
             if let Some(timer) = &self.timer {
-                let mut block = *chunks.last().unwrap();
-                let offset = timer.title.len() as u16 + 8 + 3;
-                block.x = block.width - offset;
-                block.width = offset;
-                let text = timer.show(&self.data);
-                f.render_widget(text, block);
+                line.width -= render_timer(timer, &self.data, f, line);
+            }
+            line.width -= render_goal(&self.data, f, line);
+            if self.notification.is_none()
+                && self.quick_input.is_none()
+                && self.quick_select.is_none()
+            {
+                render_completions(&self.data, f, line);
             }
         })?;
 
         Ok(())
     }
+
+    /// Draws the open input or select dialog, or else the notification, into `line`.
+    /// Vertical select dialogs list their choices over `body`.
+    fn show_prompt(&self, f: &mut Frame<impl Backend>, body: Rect, line: Rect) {
+        if let Some(input) = &self.quick_input {
+            let (text, pos) = input.show(&self.data);
+            f.render_widget(text, line);
+            f.set_cursor(line.left() + pos, line.top());
+        }
+
+        if let Some(text) = &self.notification {
+            if self.quick_input.is_none() && self.quick_select.is_none() {
+                f.render_widget(Paragraph::new(text.as_str()), line);
+            }
+        }
+
+        if let Some(input) = &self.quick_select {
+            if input.is_vertical(line.width) {
+                input.show_vertical(&self.data, f, body);
+                f.render_widget(Paragraph::new(input.hint(&self.data)), line);
+            } else {
+                f.render_widget(input.show(&self.data), line);
+            }
+        }
+    }
+
+    /// Renders one frame into an in-memory buffer. Uses `window_size`, or 80x24 when it
+    /// hasn't been set.
+    #[cfg(test)]
+    pub(crate) fn render(&mut self) -> Buffer {
+        let (width, height) = match self.data.window_size {
+            (0, _) | (_, 0) => (80, 24),
+            size => size,
+        };
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        self.show(&mut terminal).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// Renders one frame as text, one line per terminal row.
+    #[cfg(test)]
+    pub(crate) fn render_to_string(&mut self) -> String {
+        let buffer = self.render();
+        let (width, height) = (buffer.area.width, buffer.area.height);
+        let mut text = String::new();
+        for y in 0..height {
+            let line: String = (0..width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Hands an action to the automaton, except for global keys that work in any state.
+fn dispatch(machine: &mut Machine<Action, Tasker>, tasker: &mut Tasker, action: Action) {
+    let Action::Key(key) = &action;
+    if key.code == KeyCode::Char('n') && key.modifiers == KeyModifiers::CONTROL {
+        machine.push_detached(tasker, CaptureState::default());
+    } else {
+        machine.act(tasker, action);
+    }
+}
+
+/// Passes a key press through the macro recorder, then on to the automaton.
+fn handle_key(machine: &mut Machine<Action, Tasker>, tasker: &mut Tasker, key: KeyEvent) {
+    match tasker.macros.handle(key) {
+        MacroEvent::Consumed => {}
+        MacroEvent::Message(text) => tasker.notify(text),
+        MacroEvent::Pass => dispatch(machine, tasker, Action::Key(key)),
+        MacroEvent::Replay(actions) => {
+            for action in actions {
+                dispatch(machine, tasker, action);
+            }
+        }
+    }
+}
+
+/// Draws the timer at the right end of a one-line area. Returns the width it took.
+fn render_timer(timer: &Timer, data: &AppData, f: &mut Frame<impl Backend>, line: Rect) -> u16 {
+    let mut block = line;
+    let offset = timer.title.len() as u16 + 8 + 3;
+    block.x = block.width - offset;
+    block.width = offset;
+    f.render_widget(timer.show(data), block);
+    offset
+}
+
+/// Draws progress towards the daily pomodoro goal at the right end of a one-line area,
+/// if a goal is set. Returns the width used.
+fn render_goal(data: &AppData, f: &mut Frame<impl Backend>, line: Rect) -> u16 {
+    let goal = data.config.daily_pomodoro_goal;
+    if goal == 0 {
+        return 0;
+    }
+    let done = data.pomodoros_today(data.clock.wall_now());
+    let (text, style) = if done >= goal {
+        let style = Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+        (format!("✓ {}/{} today ", done, goal), style)
+    } else {
+        (format!("{}/{} today ", done, goal), Style::default())
+    };
+    let width = (text.chars().count() as u16).min(line.width);
+    let area = Rect {
+        x: line.x + line.width - width,
+        width,
+        ..line
+    };
+    f.render_widget(Paragraph::new(text).style(styled(data, style)), area);
+    width
+}
+
+/// Days covered by the completions sparkline.
+const SPARKLINE_DAYS: usize = 14;
+
+/// Draws a sparkline of tasks completed per day at the right end of a one-line area.
+/// Draws nothing if nothing was completed in that time.
+fn render_completions(data: &AppData, f: &mut Frame<impl Backend>, line: Rect) {
+    let counts = data.completions_per_day(data.clock.wall_now());
+    let width = SPARKLINE_DAYS as u16 + 1;
+    if counts.iter().all(|count| *count == 0) || line.width < width {
+        return;
+    }
+    let area = Rect {
+        x: line.x + line.width - width,
+        width: SPARKLINE_DAYS as u16,
+        ..line
+    };
+    let sparkline = Sparkline::default()
+        .data(&counts)
+        .style(styled(data, Style::default().fg(Color::Green)));
+    f.render_widget(sparkline, area);
+}
+
+/// Guesses from the environment whether the terminal shows colors. Without them,
+/// rendering falls back to text attributes only.
+fn colors_supported(var: impl Fn(&str) -> Option<String>) -> bool {
+    // https://no-color.org: any non-empty value disables colors.
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if var("COLORTERM").is_some_and(|v| !v.is_empty()) {
+        return true;
+    }
+    // Without TERM, or with TERM=dumb, even escape sequences for color are unlikely to
+    // be understood.
+    var("TERM").is_some_and(|term| !term.is_empty() && term != "dumb")
 }
 
 fn event_loop(mut terminal: Terminal<impl Backend>) -> CResult<()> {
     let mut tasker = Tasker::default();
     let mut machine = Machine::new(NormalState);
     tasker.data.window_size = terminal::size()?;
-    loop {
+    tasker.data.colors = colors_supported(|name| std::env::var(name).ok());
+    while !tasker.quit_requested {
         tasker.update();
         tasker.show(&mut terminal)?;
         // Wait up to 1s for another event
@@ -172,14 +639,13 @@ fn event_loop(mut terminal: Terminal<impl Backend>) -> CResult<()> {
                 Event::Resize(w, h) => {
                     tasker.data.window_size = (w, h);
                 }
-                Event::Key(k)
-                    if k.code == KeyCode::Char('c')
-                        && k.modifiers == crossterm::event::KeyModifiers::CONTROL =>
-                {
-                    break;
-                }
                 Event::Key(key) => {
-                    machine.act(&mut tasker, Action::Key(key));
+                    tasker.notification = None;
+                    if key == tasker.data.config.quit_key {
+                        tasker.press_quit();
+                        continue;
+                    }
+                    handle_key(&mut machine, &mut tasker, key);
                 }
                 _ => {}
             }
@@ -203,3 +669,41 @@ fn main() -> CResult<()> {
     execute!(stdout(), terminal::LeaveAlternateScreen, cursor::Show)?;
     disable_raw_mode()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_empty_list() {
+        let mut tasker = Tasker::default();
+        let screen = tasker.render_to_string();
+        assert_eq!(screen.lines().count(), 24);
+        assert!(screen.contains("No tasks — press 'n' to create one"));
+    }
+
+    #[test]
+    fn test_colors_supported() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                let var = vars.iter().find(|(key, _)| *key == name);
+                var.map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(colors_supported(env(&[("TERM", "xterm-256color")])));
+        assert!(!colors_supported(env(&[("TERM", "dumb")])));
+        assert!(!colors_supported(env(&[])));
+        assert!(colors_supported(env(&[
+            ("TERM", "dumb"),
+            ("COLORTERM", "truecolor")
+        ])));
+        assert!(!colors_supported(env(&[
+            ("TERM", "xterm"),
+            ("NO_COLOR", "1")
+        ])));
+        assert!(colors_supported(env(&[
+            ("TERM", "xterm"),
+            ("NO_COLOR", "")
+        ])));
+    }
+}
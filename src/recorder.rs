@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::Action;
+
+enum Pending {
+    Record,
+    Replay,
+}
+
+pub(crate) enum MacroEvent {
+    /// The key belongs to the recorder and shouldn't reach the automaton.
+    Consumed,
+    /// Like `Consumed`, with a message for the status line.
+    Message(String),
+    /// The key should be handled normally.
+    Pass,
+    /// The key should be replaced by these actions.
+    Replay(Vec<Action>),
+}
+
+/// Records key presses into named slots and replays them.
+///
+/// Ctrl+R followed by a slot key starts recording and Ctrl+R stops it. Ctrl+P followed
+/// by a slot key replays the slot. Replayed actions go straight to the automaton instead
+/// of back through the recorder, so a macro can never trigger another replay. Replaying
+/// while recording copies the replayed keys into the recording.
+#[derive(Default)]
+pub(crate) struct MacroRecorder {
+    slots: HashMap<char, Vec<Action>>,
+    recording: Option<(char, Vec<Action>)>,
+    pending: Option<Pending>,
+}
+
+impl MacroRecorder {
+    pub(crate) fn handle(&mut self, key: KeyEvent) -> MacroEvent {
+        if let Some(pending) = self.pending.take() {
+            let slot = match key.code {
+                KeyCode::Char(slot) => slot,
+                _ => return MacroEvent::Consumed,
+            };
+            return match pending {
+                Pending::Record => {
+                    self.recording = Some((slot, Vec::new()));
+                    MacroEvent::Message(format!("Recording macro '{}' — Ctrl+R to stop", slot))
+                }
+                Pending::Replay => match self.slots.get(&slot) {
+                    Some(actions) => {
+                        if let Some((_, recording)) = &mut self.recording {
+                            recording.extend(actions.iter().cloned());
+                        }
+                        MacroEvent::Replay(actions.clone())
+                    }
+                    None => MacroEvent::Message(format!("No macro in '{}'", slot)),
+                },
+            };
+        }
+
+        if key.modifiers == KeyModifiers::CONTROL {
+            match key.code {
+                KeyCode::Char('r') => {
+                    return match self.recording.take() {
+                        Some((slot, actions)) => {
+                            let message =
+                                format!("Saved macro '{}' ({} keys)", slot, actions.len());
+                            self.slots.insert(slot, actions);
+                            MacroEvent::Message(message)
+                        }
+                        None => {
+                            self.pending = Some(Pending::Record);
+                            MacroEvent::Consumed
+                        }
+                    };
+                }
+                KeyCode::Char('p') => {
+                    self.pending = Some(Pending::Replay);
+                    return MacroEvent::Consumed;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((_, actions)) = &mut self.recording {
+            actions.push(Action::Key(key));
+        }
+        MacroEvent::Pass
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::Harness;
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn record(recorder: &mut MacroRecorder, slot: char, keys: &str) {
+        recorder.handle(ctrl('r'));
+        recorder.handle(key(slot));
+        for c in keys.chars() {
+            assert!(matches!(recorder.handle(key(c)), MacroEvent::Pass));
+        }
+        recorder.handle(ctrl('r'));
+    }
+
+    fn replay(recorder: &mut MacroRecorder, slot: char) -> Vec<Action> {
+        assert!(matches!(recorder.handle(ctrl('p')), MacroEvent::Consumed));
+        match recorder.handle(key(slot)) {
+            MacroEvent::Replay(actions) => actions,
+            _ => panic!("slot '{}' should replay", slot),
+        }
+    }
+
+    fn codes(actions: &[Action]) -> Vec<KeyCode> {
+        actions.iter().map(|Action::Key(key)| key.code).collect()
+    }
+
+    #[test]
+    fn test_record_into_slot() {
+        let mut recorder = MacroRecorder::default();
+        recorder.handle(ctrl('r'));
+        match recorder.handle(key('a')) {
+            MacroEvent::Message(text) => assert!(text.starts_with("Recording macro 'a'")),
+            _ => panic!("should announce recording"),
+        }
+        recorder.handle(key('x'));
+        recorder.handle(key('y'));
+        match recorder.handle(ctrl('r')) {
+            MacroEvent::Message(text) => assert_eq!(text, "Saved macro 'a' (2 keys)"),
+            _ => panic!("should announce saving"),
+        }
+
+        assert_eq!(
+            codes(&replay(&mut recorder, 'a')),
+            vec![KeyCode::Char('x'), KeyCode::Char('y')]
+        );
+        assert!(matches!(recorder.handle(ctrl('p')), MacroEvent::Consumed));
+        assert!(matches!(recorder.handle(key('b')), MacroEvent::Message(_)));
+    }
+
+    #[test]
+    fn test_replay_through_dispatch() {
+        let mut h = Harness::new();
+        h.press(KeyCode::Char('r'), KeyModifiers::CONTROL)
+            .press(KeyCode::Char('a'), KeyModifiers::NONE);
+        for c in "nTask".chars() {
+            h.press(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        h.press(KeyCode::Enter, KeyModifiers::NONE)
+            .press(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(h.tasker.data.store.len(), 1);
+
+        h.press(KeyCode::Char('p'), KeyModifiers::CONTROL)
+            .press(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(h.tasker.data.store.len(), 2);
+
+        // A Ctrl+P inside a macro goes to the automaton, not back to the recorder.
+        let nested = vec![Action::Key(ctrl('p')), Action::Key(key('a'))];
+        h.tasker.macros.slots.insert('b', nested);
+        h.press(KeyCode::Char('p'), KeyModifiers::CONTROL)
+            .press(KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(h.tasker.data.store.len(), 2);
+        assert!(h.tasker.macros.pending.is_none());
+    }
+
+    #[test]
+    fn test_replay_does_not_recurse() {
+        let mut recorder = MacroRecorder::default();
+        record(&mut recorder, 'a', "xy");
+
+        // Replaying while recording copies the replayed keys, not the Ctrl+P.
+        recorder.handle(ctrl('r'));
+        recorder.handle(key('b'));
+        replay(&mut recorder, 'a');
+        recorder.handle(key('z'));
+        recorder.handle(ctrl('r'));
+        assert_eq!(
+            codes(&replay(&mut recorder, 'b')),
+            vec![KeyCode::Char('x'), KeyCode::Char('y'), KeyCode::Char('z')]
+        );
+
+        // Replaying a slot into its own recording uses the old contents once.
+        recorder.handle(ctrl('r'));
+        recorder.handle(key('a'));
+        replay(&mut recorder, 'a');
+        recorder.handle(ctrl('r'));
+        assert_eq!(
+            codes(&replay(&mut recorder, 'a')),
+            vec![KeyCode::Char('x'), KeyCode::Char('y')]
+        );
+
+        // Replayed actions never pass through the recorder again.
+        assert!(recorder.pending.is_none());
+        assert!(recorder.recording.is_none());
+    }
+}
@@ -2,11 +2,13 @@ pub(crate) mod dialogs;
 
 use crate::{
     automaton::*,
-    components::{TaskView, Timer},
+    components::{BoardView, TaskView, Timer, TrashView},
+    config::parse_task_id,
+    hooks::TimerHook,
     task::{self, Filter, TaskId},
-    Action, Pane, Tasker,
+    Action, AppData, Pane, Tasker,
 };
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyModifiers};
 
 use dialogs::*;
 
@@ -31,41 +33,101 @@ impl State for NormalState {
             KeyCode::Char('f') => {
                 return self.push(SetFilterState);
             }
-            KeyCode::Up => {
-                data.tasklist.selection = data.tasklist.selection.saturating_sub(1);
+            KeyCode::Char('g') => {
+                return self.push(GoToIdState);
+            }
+            KeyCode::Char('q') => data.press_quit(),
+            KeyCode::Char('R') => {
+                return self.push(ReplaceState::default());
+            }
+            KeyCode::Char('X') => {
+                return self.push(ExportDotState);
+            }
+            KeyCode::Char('D') => {
+                let done = Filter {
+                    status: Some(task::Status::Done),
+                    ..Filter::default()
+                };
+                match done.count(&data.data.store) {
+                    0 => data.notify("No done tasks to clear"),
+                    count => return self.push(ClearDoneState(count)),
+                }
             }
-            KeyCode::Down => {
-                if data.tasklist.selection + 1 < data.tasklist.tasks.len() {
-                    data.tasklist.selection += 1;
+            KeyCode::Char('r') => {
+                if let Some(id) = data.tasklist.selection() {
+                    return self.push(SetReminderState(id));
                 }
             }
+            KeyCode::Char('/') => {
+                return self.push(FindState::default());
+            }
+            KeyCode::Char('F') => {
+                data.filter = Filter::default();
+                data.tasklist.refresh(&data.data, &data.filter);
+                data.notify("Filter cleared");
+            }
+            KeyCode::Up => data.tasklist.select_previous(),
+            KeyCode::Down => data.tasklist.select_next(),
             KeyCode::Enter => {
                 if let Some(id) = data.tasklist.selection() {
                     return self.transition(OneTaskState(id));
                 }
             }
+            KeyCode::Tab | KeyCode::BackTab => {
+                if let Some(id) = data.last_task {
+                    return self.transition(OneTaskState(id));
+                }
+            }
             KeyCode::Char(' ') => {
                 if let Some(id) = data.tasklist.selection() {
-                    let task = data.data.store.get_task_mut(id);
-                    task.toggle_status();
+                    let now = data.data.clock.wall_now();
+                    data.data.store.get_task_mut(id).toggle_status(now);
+                    data.tasklist.refresh(&data.data, &data.filter);
                 }
             }
-            KeyCode::Char('m') => {
-                let task = data.data.store.new_task();
-                task.title = task.id.id().to_string();
-                data.tasklist.tasks.push(task.id);
-                data.tasklist.selection = data.tasklist.tasks.len() - 1;
+            KeyCode::Char('<') => {
+                if let Some(id) = data.tasklist.selection() {
+                    data.data.store.move_to_front(id);
+                    data.tasklist.refresh(&data.data, &data.filter);
+                }
+            }
+            KeyCode::Char('>') => {
+                if let Some(id) = data.tasklist.selection() {
+                    data.data.store.move_to_back(id);
+                    data.tasklist.refresh(&data.data, &data.filter);
+                }
             }
             KeyCode::Char('e') => {
                 if let Some(id) = data.tasklist.selection() {
                     return self.push(SetDescriptionState(id));
                 }
             }
+            KeyCode::Delete => {
+                if let Some(id) = data.tasklist.selection() {
+                    data.remove_task(id);
+                }
+            }
+            KeyCode::Char('t') => {
+                return self.transition(TrashState);
+            }
+            KeyCode::Char('b') => {
+                return self.transition(BoardState);
+            }
             KeyCode::Char('p') => {
                 if let Some(id) = data.tasklist.selection() {
                     return self.push(SetPomodoroState(id));
                 }
             }
+            KeyCode::Char('z') => {
+                if let Some(id) = data.tasklist.selection() {
+                    return self.push(FocusState(id));
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(id) = data.tasklist.selection() {
+                    return self.push(SetLabelState(Some(id)));
+                }
+            }
             _ => {}
         }
         ActResult::Nothing
@@ -73,6 +135,8 @@ impl State for NormalState {
 
     fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
         data.pane = Pane::Main;
+        // Tasks may have been toggled or changed in another view.
+        data.tasklist.refresh(&data.data, &data.filter);
         ActResult::Nothing
     }
 }
@@ -94,32 +158,30 @@ impl State for OneTaskState {
             Pane::OneTask(view) => view,
             _ => panic!("Wrong pane"),
         };
+        view.sync_links(&data.data);
 
         let Action::Key(key) = action;
 
         match key.code {
-            KeyCode::Esc => {
+            KeyCode::Esc | KeyCode::Tab | KeyCode::BackTab => {
                 return self.transition(NormalState);
             }
             KeyCode::Char('n') => {
                 return self.push(QuickCreateState);
             }
-            KeyCode::Up => {
-                view.link_list.selection = view.link_list.selection.saturating_sub(1);
-            }
-            KeyCode::Down => {
-                if view.link_list.selection + 1 < view.link_list.tasks.len() {
-                    view.link_list.selection += 1;
-                }
-            }
+            KeyCode::Up => view.link_list.select_previous(),
+            KeyCode::Down => view.link_list.select_next(),
             KeyCode::Enter => {
                 if let Some(id) = view.link_list.selection() {
                     return self.transition(OneTaskState(id));
                 }
             }
             KeyCode::Char(' ') => {
-                let task = data.data.store.get_task_mut(view.task_id);
-                task.toggle_status();
+                let now = data.data.clock.wall_now();
+                data.data
+                    .store
+                    .get_task_mut(view.task_id)
+                    .toggle_status(now);
             }
             KeyCode::Char('l') => {
                 return self.push(AddLinkState(self.0));
@@ -127,6 +189,26 @@ impl State for OneTaskState {
             KeyCode::Char('e') => {
                 return self.push(SetDescriptionState(self.0));
             }
+            KeyCode::Char('s') => {
+                return self.push(SplitState(self.0));
+            }
+            KeyCode::Char('p') => {
+                let timer = work_timer(self.0, &data.data);
+                if let Some(confirm) = set_timer(data, Some(timer)) {
+                    return self.push(confirm);
+                }
+            }
+            KeyCode::Char('z') => {
+                return self.push(FocusState(self.0));
+            }
+            KeyCode::Char('c') => {
+                return self.push(SetLabelState(Some(self.0)));
+            }
+            KeyCode::Char('x') => view.expand_link = !view.expand_link,
+            KeyCode::Char('v') => view.show_full = !view.show_full,
+            KeyCode::Char('r') => {
+                return self.push(SetReminderState(self.0));
+            }
             _ => {}
         }
 
@@ -134,17 +216,24 @@ impl State for OneTaskState {
     }
 
     fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        data.pane = Pane::OneTask(TaskView::new(self.0, &data.data, true));
+        // Moving between linked tasks keeps the layout.
+        let show_full = match &data.pane {
+            Pane::OneTask(view) => view.show_full,
+            _ => true,
+        };
+        data.pane = Pane::OneTask(TaskView::new(self.0, &data.data, show_full));
+        data.last_task = Some(self.0);
         ActResult::Nothing
     }
 }
 
-pub(crate) struct AddLinkState(TaskId);
+/// Picks a color label for a task, or for the filter when there's no task.
+pub(crate) struct SetLabelState(Option<TaskId>);
 
-impl State for AddLinkState {
+impl State for SetLabelState {
     type Action = Action;
     type Data = Tasker;
-    type Input = Option<TaskId>;
+    type Input = Option<String>;
     type Return = ();
 
     fn act(
@@ -152,7 +241,7 @@ impl State for AddLinkState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("AddLinkState shouldn't receive actions");
+        panic!("SetLabelState shouldn't receive actions");
     }
 
     fn resume(
@@ -160,33 +249,192 @@ impl State for AddLinkState {
         data: &mut Self::Data,
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
-        if let Some(oid) = value {
-            let id = self.0;
-            let task = data.data.store.get_task_mut(id);
-            task.links.push(oid);
-            let other_task = data.data.store.get_task_mut(oid);
-            other_task.links.push(id);
-
-            let view = match &mut data.pane {
-                Pane::OneTask(view) => view,
-                _ => panic!("Wrong pane"),
-            };
-            view.link_list.tasks.push(oid);
+        if let Some(text) = value {
+            let label = task::Label::ALL
+                .iter()
+                .copied()
+                .find(|label| label.name() == text);
+            match self.0 {
+                Some(id) => data.data.store.get_task_mut(id).label = label,
+                None => data.filter.label = label,
+            }
+            data.tasklist.refresh(&data.data, &data.filter);
         }
 
         self.pop(())
     }
 
     fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        self.push(SearchTaskState {
-            title: "Link a task".to_owned(),
-        })
+        let mut choices: Vec<_> = task::Label::ALL
+            .iter()
+            .map(|label| {
+                (
+                    label.name().to_ascii_lowercase().chars().next().unwrap(),
+                    label.name(),
+                )
+            })
+            .collect();
+        choices.push(('n', "None"));
+        self.push(QuickSelectState::new("Label".into(), choices))
     }
 }
 
-pub(crate) struct QuickCreateState;
+/// Shows nothing but one task and the timer. Any key goes back.
+pub(crate) struct FocusState(TaskId);
 
-impl State for QuickCreateState {
+impl State for FocusState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = ();
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        data.focus = Some(self.0);
+        ActResult::Nothing
+    }
+
+    fn on_exit(&mut self, data: &mut Self::Data) {
+        data.focus = None;
+    }
+}
+
+pub(crate) struct TrashState;
+
+impl State for TrashState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = ();
+    type Return = ();
+
+    fn act(
+        &mut self,
+        data: &mut Self::Data,
+        action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let view = match &mut data.pane {
+            Pane::Trash(view) => view,
+            _ => panic!("Wrong pane"),
+        };
+
+        let Action::Key(key) = action;
+        let len = data.data.store.trash().len();
+
+        match key.code {
+            KeyCode::Esc => {
+                return self.transition(NormalState);
+            }
+            KeyCode::Up => {
+                view.selection = view.selection.saturating_sub(1);
+            }
+            KeyCode::Down if view.selection + 1 < len => {
+                view.selection += 1;
+            }
+            KeyCode::Char('r') => {
+                data.data.store.restore_task(view.selection);
+                data.tasklist.refresh(&data.data, &data.filter);
+            }
+            KeyCode::Char('x') => {
+                data.data.store.delete_from_trash(view.selection);
+            }
+            KeyCode::Char('c') => {
+                data.data.store.empty_trash();
+            }
+            _ => {}
+        }
+        // Keep the selection on an entry after the trash shrinks.
+        let len = data.data.store.trash().len();
+        view.selection = view.selection.min(len.saturating_sub(1));
+
+        ActResult::Nothing
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        data.pane = Pane::Trash(TrashView::default());
+        ActResult::Nothing
+    }
+}
+
+pub(crate) struct BoardState;
+
+impl State for BoardState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = ();
+    type Return = ();
+
+    fn act(
+        &mut self,
+        data: &mut Self::Data,
+        action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let view = match &mut data.pane {
+            Pane::Board(view) => view,
+            _ => panic!("Wrong pane"),
+        };
+
+        let Action::Key(key) = action;
+
+        match key.code {
+            KeyCode::Esc => {
+                return self.transition(NormalState);
+            }
+            KeyCode::Left if key.modifiers == KeyModifiers::SHIFT => {
+                view.move_selection(&mut data.data, &data.filter, -1);
+            }
+            KeyCode::Right if key.modifiers == KeyModifiers::SHIFT => {
+                view.move_selection(&mut data.data, &data.filter, 1);
+            }
+            KeyCode::Left => {
+                view.column = view.column.saturating_sub(1);
+            }
+            KeyCode::Right => {
+                view.column = (view.column + 1).min(view.columns.len() - 1);
+            }
+            // Terminals send Shift+Tab as `ESC [ Z`, which crossterm reports as BackTab
+            // (with the SHIFT modifier set) in raw mode, so only the code is matched.
+            KeyCode::Tab => {
+                view.column = (view.column + 1) % view.columns.len();
+            }
+            KeyCode::BackTab => {
+                view.column = (view.column + view.columns.len() - 1) % view.columns.len();
+            }
+            KeyCode::Up => view.list().select_previous(),
+            KeyCode::Down => view.list().select_next(),
+            KeyCode::Enter => {
+                if let Some(id) = view.selection() {
+                    return self.transition(OneTaskState(id));
+                }
+            }
+            KeyCode::Char(' ') => {
+                if let Some(id) = view.selection() {
+                    let now = data.data.clock.wall_now();
+                    data.data.store.get_task_mut(id).toggle_status(now);
+                    view.refresh(&data.data, &data.filter);
+                }
+            }
+            _ => {}
+        }
+
+        ActResult::Nothing
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        data.pane = Pane::Board(BoardView::new(&data.data, &data.filter));
+        ActResult::Nothing
+    }
+}
+
+pub(crate) struct GoToIdState;
+
+impl State for GoToIdState {
     type Action = Action;
     type Data = Tasker;
     type Input = Option<String>;
@@ -197,7 +445,7 @@ impl State for QuickCreateState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("QuickCreateState shouldn't receive actions");
+        panic!("GoToIdState shouldn't receive actions");
     }
 
     fn resume(
@@ -205,27 +453,30 @@ impl State for QuickCreateState {
         data: &mut Self::Data,
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
-        if let Some(text) = value {
-            let task = data.data.store.new_task();
-            task.title = text;
-            data.tasklist.tasks.push(task.id);
-            data.tasklist.selection = data.tasklist.tasks.len() - 1;
+        // The validator only lets known ids through. Empty input just closes the dialog.
+        let id = value.and_then(|text| parse_task_id(text.trim(), &data.data.config));
+        if let Some(id) = id.and_then(|id| data.data.store.lookup(id)) {
+            return self.transition(OneTaskState(id));
         }
 
         self.pop(())
     }
 
     fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        self.push(QuickInputState::new("Title"))
+        self.push(QuickInputState::new("Go to id").validator(|data, text| {
+            let id = parse_task_id(text, &data.config).ok_or("not an id")?;
+            let id = data.store.lookup(id).ok_or("no such task")?;
+            Ok(data.store.get_task(id).title.clone())
+        }))
     }
 }
 
-pub(crate) struct SetDescriptionState(TaskId);
+pub(crate) struct AddLinkState(TaskId);
 
-impl State for SetDescriptionState {
+impl State for AddLinkState {
     type Action = Action;
     type Data = Tasker;
-    type Input = Option<String>;
+    type Input = Option<TaskId>;
     type Return = ();
 
     fn act(
@@ -233,7 +484,7 @@ impl State for SetDescriptionState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("SetDescriptionState shouldn't receive actions");
+        panic!("AddLinkState shouldn't receive actions");
     }
 
     fn resume(
@@ -241,25 +492,27 @@ impl State for SetDescriptionState {
         data: &mut Self::Data,
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
-        if let Some(text) = value {
+        if let Some(oid) = value {
             let id = self.0;
             let task = data.data.store.get_task_mut(id);
-            task.description = text;
+            task.links.push(oid);
+            let other_task = data.data.store.get_task_mut(oid);
+            other_task.links.push(id);
         }
 
         self.pop(())
     }
 
-    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        let id = self.0;
-        let task = data.data.store.get_task_mut(id);
-        self.push(QuickInputState::new("Description").text(task.description.clone()))
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(SearchTaskState {
+            title: "Link a task".to_owned(),
+        })
     }
 }
 
-pub(crate) struct SetFilterState;
+pub(crate) struct QuickCreateState;
 
-impl State for SetFilterState {
+impl State for QuickCreateState {
     type Action = Action;
     type Data = Tasker;
     type Input = Option<String>;
@@ -270,7 +523,7 @@ impl State for SetFilterState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("SetFilterState shouldn't receive actions");
+        panic!("QuickCreateState shouldn't receive actions");
     }
 
     fn resume(
@@ -279,35 +532,40 @@ impl State for SetFilterState {
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
         if let Some(text) = value {
-            if text == "Title" {
-                return self.replace(SetFilterTitleState);
-            }
-            if text == "Todo" {
-                data.filter.status = Some(task::Status::Todo);
-            }
-            if text == "Done" {
-                data.filter.status = Some(task::Status::Done);
-            }
-            if text == "Clear" {
-                data.filter = Filter::default();
+            if data.data.config.warn_duplicate_titles {
+                let existing = data.data.store.find_by_title(&text);
+                if let Some(&existing) = existing.first() {
+                    return self.replace(ConfirmDuplicateState {
+                        title: text,
+                        existing,
+                    });
+                }
             }
-            data.tasklist.apply_filter(&data.data, &data.filter);
+            add_task(data, text);
         }
 
         self.pop(())
     }
 
-    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        self.push(QuickSelectState::new(
-            "Filter".into(),
-            vec![('t', "Title"), ('d', "Todo"), ('D', "Done"), ('c', "Clear")],
-        ))
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new("Title").max_len(data.data.config.title_max_len))
     }
 }
 
-pub(crate) struct SetFilterTitleState;
+/// Creates a task and selects it at the end of the list.
+fn add_task(data: &mut Tasker, title: String) {
+    let id = data.create_task(title);
+    data.tasklist.tasks.push(id);
+    data.tasklist.selection = data.tasklist.tasks.len() - 1;
+}
 
-impl State for SetFilterTitleState {
+/// Asks what to do when a new task's title is already taken.
+pub(crate) struct ConfirmDuplicateState {
+    title: String,
+    existing: TaskId,
+}
+
+impl State for ConfirmDuplicateState {
     type Action = Action;
     type Data = Tasker;
     type Input = Option<String>;
@@ -318,42 +576,39 @@ impl State for SetFilterTitleState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("SetFilterTitleState shouldn't receive actions");
+        panic!("ConfirmDuplicateState shouldn't receive actions");
     }
 
     fn resume(
-        &mut self,
-        _data: &mut Self::Data,
-        _value: Self::Input,
-    ) -> ActResult<Self::Action, Self::Data> {
-        self.pop(())
-    }
-
-    fn on_yield(
         &mut self,
         data: &mut Self::Data,
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
-        if let Some(text) = value {
-            data.filter.title = text;
-            data.tasklist.apply_filter(&data.data, &data.filter);
+        match value.as_deref() {
+            Some("Create anyway") => add_task(data, std::mem::take(&mut self.title)),
+            Some("Open existing") => return self.transition(OneTaskState(self.existing)),
+            _ => {}
         }
 
-        ActResult::Nothing
+        self.pop(())
     }
 
-    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        self.push(
-            QuickInputState::new("Filter [Title]")
-                .text(data.filter.title.clone())
-                .continuous(true),
-        )
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickSelectState::new(
+            format!("'{}' already exists", self.title.trim()),
+            vec![('c', "Create anyway"), ('o', "Open existing")],
+        ))
     }
 }
 
-pub(crate) struct SetPomodoroState(TaskId);
+/// Find and replace in the titles and descriptions of the tasks the filter matches.
+/// Asks for the search text first and then for the replacement.
+#[derive(Default)]
+pub(crate) struct ReplaceState {
+    find: Option<String>,
+}
 
-impl State for SetPomodoroState {
+impl State for ReplaceState {
     type Action = Action;
     type Data = Tasker;
     type Input = Option<String>;
@@ -364,7 +619,7 @@ impl State for SetPomodoroState {
         _data: &mut Self::Data,
         _action: Self::Action,
     ) -> ActResult<Self::Action, Self::Data> {
-        panic!("SetPomodoroState shouldn't receive actions");
+        panic!("ReplaceState shouldn't receive actions");
     }
 
     fn resume(
@@ -372,41 +627,364 @@ impl State for SetPomodoroState {
         data: &mut Self::Data,
         value: Self::Input,
     ) -> ActResult<Self::Action, Self::Data> {
-        let id = self.0;
-        if let Some(text) = value {
-            if text == "Start" {
-                data.timer = Some(Timer::trigger_in(
-                    "WORK",
-                    std::time::Duration::from_secs(60 * 25),
-                    move |data| {
-                        let task = data.store.get_task_mut(id);
-                        task.pomodoros += 1;
-                    },
-                ));
-            }
-            if text == "Break 5m" {
-                data.timer = Some(Timer::trigger_in(
-                    "BREAK",
-                    std::time::Duration::from_secs(60 * 5),
-                    |_| {},
-                ));
-            }
-            if text == "Break 10m" {
-                data.timer = Some(Timer::trigger_in(
-                    "BREAK",
-                    std::time::Duration::from_secs(60 * 10),
-                    |_| {},
-                ));
+        let text = match value {
+            Some(text) => text,
+            None => return self.pop(()),
+        };
+        let find = match self.find.take() {
+            Some(find) => find,
+            None if text.is_empty() => return self.pop(()),
+            None => {
+                let title = format!("Replace '{}' with", text);
+                self.find = Some(text);
+                return self.push(QuickInputState::new(title));
             }
-            if text == "Test" {
-                data.timer = Some(Timer::trigger_in(
-                    "TEST",
-                    std::time::Duration::from_secs(5),
-                    |_| {},
-                ));
+        };
+
+        let mut changed = 0;
+        for id in data.filter.apply(&data.data.store) {
+            let task = data.data.store.get_task(id);
+            if !task.title.contains(&find) && !task.description.contains(&find) {
+                continue;
+            }
+            let task = data.data.store.get_task_mut(id);
+            task.title = task.title.replace(&find, &text);
+            task.description = task.description.replace(&find, &text);
+            changed += 1;
+        }
+        data.tasklist.refresh(&data.data, &data.filter);
+        data.notify(format!("Replaced '{}' in {} tasks", find, changed));
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new("Find"))
+    }
+}
+
+/// Reminders are at most this far ahead.
+const MAX_REMINDER_MINUTES: u64 = 60 * 24 * 7;
+
+/// Reads a reminder time: a number of minutes from now, or the next time the local
+/// clock shows e.g. "15:00", "3pm" or "3:30pm". Zero minutes means no reminder.
+fn parse_reminder(text: &str, data: &AppData) -> Result<Option<std::time::SystemTime>, String> {
+    let text = text.trim().to_lowercase();
+    let now = data.clock.wall_now();
+    if let Ok(minutes) = text.parse::<u64>() {
+        if minutes > MAX_REMINDER_MINUTES {
+            return Err("at most a week".into());
+        }
+        return Ok((minutes > 0).then(|| now + std::time::Duration::from_secs(60 * minutes)));
+    }
+
+    let (time, pm) = match (text.strip_suffix("am"), text.strip_suffix("pm")) {
+        (Some(time), _) => (time.trim(), Some(false)),
+        (_, Some(time)) => (time.trim(), Some(true)),
+        _ => (text.as_str(), None),
+    };
+    let (hour, minute) = match time.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>(), minute.parse::<u32>()),
+        None if pm.is_some() => (time.parse::<u32>(), Ok(0)),
+        None => return Err("minutes, or a time like 15:00".into()),
+    };
+    let (mut hour, minute) = match (hour, minute) {
+        (Ok(hour), Ok(minute)) => (hour, minute),
+        _ => return Err("not a time".into()),
+    };
+    if let Some(pm) = pm {
+        if hour == 0 || hour > 12 {
+            return Err("not a time".into());
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    if hour > 23 || minute > 59 {
+        return Err("not a time".into());
+    }
+    let offset = data.clock.utc_offset(now);
+    Ok(Some(task::next_time_of_day(now, offset, hour, minute)))
+}
+
+/// Sets a task's reminder, in minutes from now or at a time of day. Zero clears it.
+pub(crate) struct SetReminderState(TaskId);
+
+impl State for SetReminderState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetReminderState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        // The validator only lets valid times through. Empty input changes nothing.
+        if let Some(Ok(reminder)) = value.map(|text| parse_reminder(&text, &data.data)) {
+            data.data.store.get_task_mut(self.0).reminder = reminder;
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let input = QuickInputState::new("Remind in minutes or at").validator(|data, text| {
+            Ok(match parse_reminder(text, data)? {
+                Some(time) => {
+                    let left = time
+                        .duration_since(data.clock.wall_now())
+                        .unwrap_or_default();
+                    format!("in {} min", left.as_secs().div_ceil(60))
+                }
+                None => "no reminder".into(),
+            })
+        });
+        self.push(input)
+    }
+}
+
+/// Asks before moving every done task to the trash.
+pub(crate) struct ClearDoneState(usize);
+
+impl State for ClearDoneState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("ClearDoneState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if value.as_deref() == Some("Clear") {
+            let removed = data.data.store.remove_done();
+            if data.last_task.is_some_and(|id| removed.contains(&id)) {
+                data.last_task = None;
+            }
+            data.tasklist.refresh(&data.data, &data.filter);
+            data.notify(format!("Moved {} done tasks to the trash", removed.len()));
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickSelectState::new(
+            format!("Move {} done tasks to the trash?", self.0),
+            vec![('y', "Clear"), ('n', "Keep")],
+        ))
+    }
+}
+
+/// Writes the task graph to a Graphviz DOT file.
+pub(crate) struct ExportDotState;
+
+impl State for ExportDotState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("ExportDotState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(path) = value.filter(|path| !path.trim().is_empty()) {
+            match std::fs::write(path.trim(), data.data.store.export_dot()) {
+                Ok(()) => data.notify(format!("Exported task graph to {}", path.trim())),
+                Err(err) => data.notify(format!("Export failed: {}", err)),
+            }
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new("Export DOT to").text("tasks.dot".into()))
+    }
+}
+
+/// Creates a linked task from every non-empty line of a task's description.
+pub(crate) struct SplitState(TaskId);
+
+impl State for SplitState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SplitState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let clear = match value.as_deref() {
+            Some("Split") => false,
+            Some("Split and clear description") => true,
+            _ => return self.pop(()),
+        };
+
+        let id = self.0;
+        let lines: Vec<String> = split_lines(&data.data.store.get_task(id).description);
+        let mut created = Vec::new();
+        for line in lines {
+            let new = data.create_task(line);
+            data.data.store.get_task_mut(new).links.push(id);
+            created.push(new);
+        }
+        let task = data.data.store.get_task_mut(id);
+        task.links.extend(&created);
+        if clear {
+            task.description.clear();
+        }
+
+        data.tasklist.refresh(&data.data, &data.filter);
+        data.notify(format!("Created {} linked tasks", created.len()));
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let count = split_lines(&data.data.store.get_task(self.0).description).len();
+        if count == 0 {
+            data.notify("Nothing to split — the description is empty");
+            return self.pop(());
+        }
+        self.push(QuickSelectState::new(
+            format!("Split into {} tasks", count),
+            vec![('s', "Split"), ('c', "Split and clear description")],
+        ))
+    }
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+pub(crate) struct SetDescriptionState(TaskId);
+
+impl State for SetDescriptionState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetDescriptionState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value {
+            let id = self.0;
+            let task = data.data.store.get_task_mut(id);
+            task.description = text;
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let id = self.0;
+        let task = data.data.store.get_task(id);
+        self.push(QuickInputState::new("Description").text(task.description.clone()))
+    }
+}
+
+pub(crate) struct SetFilterState;
+
+impl State for SetFilterState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetFilterState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value {
+            if text == "Title" {
+                return self.replace(SetFilterTitleState);
+            }
+            if text == "Expression" {
+                return self.replace(SetFilterExprState);
+            }
+            if text == "Save" {
+                return self.replace(SaveFilterState);
+            }
+            if text == "Load" {
+                if data.saved_filters.is_empty() {
+                    data.notify("No saved filters");
+                    return self.pop(());
+                }
+                return self.replace(LoadFilterState);
+            }
+            if text == "Todo" {
+                data.filter.status = Some(task::Status::Todo);
+            }
+            if text == "Done" {
+                data.filter.status = Some(task::Status::Done);
+            }
+            if text == "Orphans" {
+                data.filter.orphans_only = true;
+            }
+            if text == "Label" {
+                return self.replace(SetLabelState(None));
             }
             if text == "Clear" {
-                data.timer = None;
+                data.filter = Filter::default();
             }
             data.tasklist.apply_filter(&data.data, &data.filter);
         }
@@ -415,15 +993,578 @@ impl State for SetPomodoroState {
     }
 
     fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(
+            QuickSelectState::new(
+                "Filter".into(),
+                vec![
+                    ('t', "Title"),
+                    ('e', "Expression"),
+                    ('d', "Todo"),
+                    ('D', "Done"),
+                    ('o', "Orphans"),
+                    ('L', "Label"),
+                    ('c', "Clear"),
+                    ('s', "Save"),
+                    ('l', "Load"),
+                ],
+            )
+            .vertical([
+                "titles containing some text",
+                "e.g. todo and (docs or tests)",
+                "only unfinished tasks",
+                "only finished tasks",
+                "only tasks without links",
+                "only tasks with a color label",
+                "show everything",
+                "keep this filter under a name",
+                "apply a saved filter",
+            ]),
+        )
+    }
+}
+
+pub(crate) struct SaveFilterState;
+
+impl State for SaveFilterState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SaveFilterState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(name) = value {
+            let name = name.trim();
+            if !name.is_empty() {
+                data.saved_filters
+                    .insert(name.to_owned(), data.filter.clone());
+                data.notify(format!("Saved filter '{}'", name));
+            }
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new("Filter name"))
+    }
+}
+
+pub(crate) struct LoadFilterState;
+
+impl State for LoadFilterState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("LoadFilterState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(filter) = value.and_then(|name| data.saved_filters.get(&name)) {
+            data.filter = filter.clone();
+            data.tasklist.apply_filter(&data.data, &data.filter);
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let mut names: Vec<_> = data.saved_filters.keys().cloned().collect();
+        names.sort();
+        // A few filters are picked with 1-9. Past that, they're picked by typing.
+        let filterable = names.len() > 9;
+        let keys = ('1'..='9').chain(std::iter::repeat(' '));
+        self.push(
+            QuickSelectState::new("Load filter".into(), keys.zip(names)).filterable(filterable),
+        )
+    }
+}
+
+pub(crate) struct SetFilterTitleState;
+
+impl State for SetFilterTitleState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetFilterTitleState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        _data: &mut Self::Data,
+        _value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        self.pop(())
+    }
+
+    fn on_yield(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value {
+            data.filter.title = text;
+            data.tasklist.apply_filter(&data.data, &data.filter);
+        }
+
+        ActResult::Nothing
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(
+            QuickInputState::new("Filter [Title]")
+                .text(data.filter.title.clone())
+                .continuous(true),
+        )
+    }
+}
+
+/// Jumps to tasks in the list whose titles contain a query, without filtering. Once the
+/// query is entered, n and N go to the next and previous match and any other key leaves.
+#[derive(Default)]
+pub(crate) struct FindState {
+    query: Option<String>,
+    /// The selection before the search, restored if it's cancelled.
+    origin: usize,
+}
+
+impl State for FindState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        data: &mut Self::Data,
+        action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let Action::Key(key) = action;
+        let query = self
+            .query
+            .as_ref()
+            .expect("FindState acts only after input");
+        let backward = match key.code {
+            KeyCode::Char('n') => false,
+            KeyCode::Char('N') => true,
+            _ => return self.pop(()),
+        };
+        if !data.tasklist.find(&data.data, query, backward, true) {
+            data.notify(format!("No matches for '{}'", query));
+        }
+        ActResult::Nothing
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        match value {
+            Some(text) if !text.is_empty() => {
+                data.notify(format!("/{} — n/N for next/previous", text));
+                self.query = Some(text);
+                ActResult::Nothing
+            }
+            _ => {
+                data.tasklist.selection = self.origin;
+                self.pop(())
+            }
+        }
+    }
+
+    fn on_yield(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value {
+            data.tasklist.selection = self.origin;
+            data.tasklist.find(&data.data, &text, false, false);
+            data.tasklist.find = Some(text);
+        }
+        ActResult::Nothing
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.origin = data.tasklist.selection;
+        self.push(QuickInputState::new("Find").continuous(true))
+    }
+
+    fn on_exit(&mut self, data: &mut Self::Data) {
+        data.tasklist.find = None;
+    }
+}
+
+pub(crate) struct SetFilterExprState;
+
+impl State for SetFilterExprState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetFilterExprState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value {
+            if text.trim().is_empty() {
+                data.filter.expr = None;
+            } else {
+                match task::FilterExpr::parse(&text) {
+                    Ok(expr) => data.filter.expr = Some(Box::new(expr)),
+                    Err(err) => data.notify(format!("Invalid filter: {}", err)),
+                }
+            }
+            data.tasklist.apply_filter(&data.data, &data.filter);
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new("Filter [Expression]"))
+    }
+}
+
+/// A 25 minute WORK timer that credits a pomodoro to the task when it completes.
+fn work_timer(id: TaskId, data: &AppData) -> Timer {
+    let duration = std::time::Duration::from_secs(60 * 25);
+    Timer::trigger_in("WORK", duration, &*data.clock, move |data| {
+        if data.store.contains(id) {
+            let task = data.store.get_task_mut(id);
+            task.pomodoros += 1;
+            task.time_spent += duration;
+        }
+        let now = data.clock.wall_now();
+        data.pomodoro_log.push(now);
+    })
+    .task(id)
+    .sound(data.config.work_sound.clone())
+}
+
+fn break_timer(minutes: u64, data: &AppData) -> Timer {
+    Timer::trigger_in(
+        "BREAK",
+        std::time::Duration::from_secs(60 * minutes),
+        &*data.clock,
+        |_| {},
+    )
+    .sound(data.config.break_sound.clone())
+}
+
+/// Starts a break of a chosen length.
+pub(crate) struct CustomBreakState;
+
+impl State for CustomBreakState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<u64>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("CustomBreakState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(minutes) = value {
+            let timer = break_timer(minutes, &data.data);
+            if let Some(confirm) = set_timer(data, Some(timer)) {
+                return self.replace(confirm);
+            }
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(NumberInputState::new("Break minutes").range(1, 120))
+    }
+}
+
+/// Running timers older than this ask before being cleared.
+const CONFIRM_CLEAR_AFTER: std::time::Duration = std::time::Duration::from_secs(60 * 5);
+
+pub(crate) struct SetPomodoroState(TaskId);
+
+impl State for SetPomodoroState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("SetPomodoroState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let id = self.0;
+        if let Some(text) = value {
+            let timer = match text.as_str() {
+                "Start" => Some(work_timer(id, &data.data)),
+                "Break 5m" => Some(break_timer(5, &data.data)),
+                "Break 10m" => Some(break_timer(10, &data.data)),
+                "Break…" => return self.replace(CustomBreakState),
+                "Start with hook…" => return self.replace(StartWithHookState(id)),
+                "Test" => Some(Timer::trigger_in(
+                    "TEST",
+                    std::time::Duration::from_secs(5),
+                    &*data.data.clock,
+                    |_| {},
+                )),
+                "Clear" => None,
+                _ => return self.pop(()),
+            };
+            if let Some(confirm) = set_timer(data, timer) {
+                return self.replace(confirm);
+            }
+            data.tasklist.refresh(&data.data, &data.filter);
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let choices = vec![
+            ('p', "Start"),
+            ('h', "Start with hook…"),
+            ('b', "Break 5m"),
+            ('B', "Break 10m"),
+            ('m', "Break…"),
+            ('t', "Test"),
+            ('c', "Clear"),
+        ];
+        self.push(QuickSelectState::new("Pomodoro".into(), choices))
+    }
+}
+
+/// Starts a work timer that runs one of the configured hooks when it completes.
+pub(crate) struct StartWithHookState(TaskId);
+
+impl State for StartWithHookState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("StartWithHookState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let name = match value {
+            Some(name) => name,
+            None => return self.pop(()),
+        };
+        if name == "Command…" {
+            return self.replace(TypeHookState::new(self.0, "Command", TimerHook::Command));
+        }
+        if name == "Log file…" {
+            return self.replace(TypeHookState::new(self.0, "Log file", |path| {
+                TimerHook::Log(path.into())
+            }));
+        }
+        let hooks = &data.data.config.timer_hooks;
+        if let Some((name, hook)) = hooks.iter().find(|(x, _)| *x == name).cloned() {
+            let timer = work_timer(self.0, &data.data).hook(name, hook);
+            if let Some(confirm) = set_timer(data, Some(timer)) {
+                return self.replace(confirm);
+            }
+            data.tasklist.refresh(&data.data, &data.filter);
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let mut choices: Vec<_> = ('1'..='9')
+            .zip(&data.data.config.timer_hooks)
+            .map(|(key, (name, _))| (key, name.clone()))
+            .collect();
+        choices.push(('c', "Command…".into()));
+        choices.push(('l', "Log file…".into()));
+        self.push(QuickSelectState::new("Hook".into(), choices))
+    }
+}
+
+/// Asks for the command or file of a hook that isn't in the config.
+pub(crate) struct TypeHookState {
+    task: TaskId,
+    title: &'static str,
+    make: fn(String) -> TimerHook,
+}
+
+impl TypeHookState {
+    fn new(task: TaskId, title: &'static str, make: fn(String) -> TimerHook) -> Self {
+        Self { task, title, make }
+    }
+}
+
+impl State for TypeHookState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("TypeHookState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value.filter(|text| !text.trim().is_empty()) {
+            let text = text.trim().to_owned();
+            let hook = (self.make)(text.clone());
+            let timer = work_timer(self.task, &data.data).hook(text, hook);
+            if let Some(confirm) = set_timer(data, Some(timer)) {
+                return self.replace(confirm);
+            }
+            data.tasklist.refresh(&data.data, &data.filter);
+        }
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, _data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.push(QuickInputState::new(self.title))
+    }
+}
+
+/// Replaces the running timer with `timer`, or just clears it with `None`. Goes through
+/// `Tasker::clear_timer`, so cut-short work is credited. If the running timer has gone
+/// on for a while, nothing changes yet and the confirmation to show is returned instead.
+fn set_timer(data: &mut Tasker, timer: Option<Timer>) -> Option<ConfirmClearTimerState> {
+    let now = data.data.clock.now();
+    if let Some(running) = &data.timer {
+        let elapsed = running.elapsed(now);
+        if !running.triggered && elapsed > CONFIRM_CLEAR_AFTER {
+            return Some(ConfirmClearTimerState {
+                elapsed,
+                next: timer,
+            });
+        }
+    }
+    data.clear_timer();
+    data.timer = timer;
+    None
+}
+
+/// Asks before discarding a timer that has been running for a while.
+pub(crate) struct ConfirmClearTimerState {
+    elapsed: std::time::Duration,
+    /// Started in place of the discarded timer.
+    next: Option<Timer>,
+}
+
+impl State for ConfirmClearTimerState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("ConfirmClearTimerState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if value.as_deref() == Some("Discard") {
+            data.clear_timer();
+            data.timer = self.next.take().map(|mut timer| {
+                // Don't count the time spent deciding.
+                timer.restart(&*data.data.clock);
+                timer
+            });
+            data.tasklist.refresh(&data.data, &data.filter);
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let minutes = self.elapsed.as_secs() / 60;
+        let title = if data.data.config.credit_partial_work {
+            format!("Stop after {} min of focus?", minutes)
+        } else {
+            format!("Discard {} min of focus?", minutes)
+        };
         self.push(QuickSelectState::new(
-            "Pomodoro".into(),
-            vec![
-                ('p', "Start"),
-                ('b', "Break 5m"),
-                ('B', "Break 10m"),
-                ('t', "Test"),
-                ('c', "Clear"),
-            ],
+            title,
+            vec![('y', "Discard"), ('n', "Keep")],
         ))
     }
 }
@@ -1,7 +1,8 @@
 use crate::automaton::*;
 use crate::task::{Filter, TaskId};
-use crate::{Action, QuickInput, QuickSelect, Search, TaskList, Tasker};
+use crate::{Action, AppData, QuickInput, QuickSelect, Search, TaskList, Tasker};
 use crossterm::event::KeyCode;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub(crate) struct SearchTaskState {
     pub(crate) title: String,
@@ -36,7 +37,7 @@ impl State for SearchTaskState {
 
         if send {
             search.filter.title = input.text.clone();
-            list.apply_filter(&data.data, &search.filter);
+            list.apply_search(&data.data, &search.filter);
         }
 
         if key.code == KeyCode::Enter {
@@ -44,10 +45,10 @@ impl State for SearchTaskState {
         }
 
         if key.code == KeyCode::Up {
-            list.selection = list.selection.saturating_sub(1);
+            list.select_previous();
         }
-        if key.code == KeyCode::Down && list.selection + 1 < list.tasks.len() {
-            list.selection += 1;
+        if key.code == KeyCode::Down {
+            list.select_next();
         }
 
         if key.code == KeyCode::Esc {
@@ -58,7 +59,9 @@ impl State for SearchTaskState {
     }
 
     fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        let mut list = TaskList::default().title(&self.title);
+        let mut list = TaskList::default()
+            .title(&self.title)
+            .empty_text("No matches");
         list.apply_filter(&data.data, &Filter::default());
         data.quick_input = Some(QuickInput::new("Search"));
         data.search = Some(Search {
@@ -74,11 +77,18 @@ impl State for SearchTaskState {
     }
 }
 
-#[derive(Debug, Default)]
+/// Checks the text of a `QuickInputState`, returning what it means or why it's invalid.
+pub(crate) type Validator = Box<dyn Fn(&AppData, &str) -> Result<String, String>>;
+
+#[derive(Default)]
 pub(crate) struct QuickInputState {
     pub(crate) title: String,
     pub(crate) continuous: bool,
     pub(crate) text: String,
+    /// Run on every edit. Invalid text is neither yielded nor accepted with Enter.
+    pub(crate) validator: Option<Validator>,
+    /// Maximum length in graphemes. Further characters are ignored.
+    pub(crate) max_len: Option<usize>,
 }
 
 impl QuickInputState {
@@ -87,6 +97,28 @@ impl QuickInputState {
             title: title.into(),
             text: String::new(),
             continuous: false,
+            validator: None,
+            max_len: None,
+        }
+    }
+
+    pub(crate) fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub(crate) fn validator(
+        mut self,
+        validator: impl Fn(&AppData, &str) -> Result<String, String> + 'static,
+    ) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    fn validate(&self, data: &AppData, text: &str) -> Option<Result<String, String>> {
+        match &self.validator {
+            Some(validator) if !text.is_empty() => Some(validator(data, text)),
+            _ => None,
         }
     }
 
@@ -118,17 +150,28 @@ impl State for QuickInputState {
 
         let mut send = false;
         if let KeyCode::Char(c) = key.code {
-            input.text.push(c);
-            send = true;
+            let full = self
+                .max_len
+                .is_some_and(|max| input.text.graphemes(true).count() >= max);
+            if !full {
+                input.text.push(c);
+                send = true;
+            }
         }
         if key.code == KeyCode::Backspace {
             input.text.pop();
             send = true;
         }
+        if send {
+            input.feedback = self.validate(&data.data, &input.text);
+        }
+        let valid = !matches!(input.feedback, Some(Err(_)));
 
         if send && self.continuous {
-            return self.do_yield(Some(input.text.clone()));
-        } else if key.code == KeyCode::Enter {
+            if valid {
+                return self.do_yield(Some(input.text.clone()));
+            }
+        } else if key.code == KeyCode::Enter && valid {
             return self.pop(Some(input.text.clone()));
         }
 
@@ -140,7 +183,97 @@ impl State for QuickInputState {
     }
 
     fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        data.quick_input = Some(QuickInput::new(&self.title).text(self.text.clone()));
+        let mut input = QuickInput::new(&self.title).text(self.text.clone());
+        input.max_len = self.max_len;
+        input.feedback = self.validate(&data.data, &self.text);
+        data.quick_input = Some(input);
+        ActResult::Nothing
+    }
+
+    fn on_exit(&mut self, data: &mut Self::Data) {
+        data.quick_input = None;
+    }
+}
+
+/// Like `QuickInputState`, but only takes digits and returns the number entered.
+#[derive(Debug, Default)]
+pub(crate) struct NumberInputState {
+    pub(crate) title: String,
+    /// Entered numbers are clamped to this range.
+    pub(crate) min: Option<u64>,
+    pub(crate) max: Option<u64>,
+}
+
+impl NumberInputState {
+    pub(crate) fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            min: None,
+            max: None,
+        }
+    }
+
+    pub(crate) fn range(mut self, min: u64, max: u64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    fn clamp(&self, value: u64) -> u64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+}
+
+impl State for NumberInputState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = ();
+    type Return = Option<u64>;
+
+    fn act(
+        &mut self,
+        data: &mut Self::Data,
+        action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        let Action::Key(key) = action;
+
+        let input = data.quick_input.as_mut().unwrap();
+
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                input.text.push(c);
+                // Digits that would overflow are dropped like any other character.
+                if input.text.parse::<u64>().is_err() {
+                    input.text.pop();
+                }
+            }
+            KeyCode::Backspace => {
+                input.text.pop();
+            }
+            KeyCode::Enter => {
+                if let Ok(value) = input.text.parse() {
+                    return self.pop(Some(self.clamp(value)));
+                }
+            }
+            KeyCode::Esc => return self.pop(None),
+            _ => {}
+        }
+
+        input.feedback = match input.text.parse() {
+            Ok(value) if self.clamp(value) != value => Some(Ok(format!("{}", self.clamp(value)))),
+            _ => None,
+        };
+
+        ActResult::Nothing
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        let title = match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{} ({}-{})", self.title, min, max),
+            _ => self.title.clone(),
+        };
+        data.quick_input = Some(QuickInput::new(title));
         ActResult::Nothing
     }
 
@@ -149,10 +282,18 @@ impl State for QuickInputState {
     }
 }
 
+/// Picks one of a few choices by hotkey, or by moving the highlight with the arrow
+/// keys and pressing Enter. The first choice starts highlighted in every layout, so
+/// Enter right away picks it.
 #[derive(Debug, Default)]
 pub(crate) struct QuickSelectState {
     pub(crate) title: String,
     pub(crate) choices: Vec<(char, String)>,
+    pub(crate) descriptions: Vec<String>,
+    pub(crate) vertical: bool,
+    /// Typing narrows the choices instead of picking by hotkey.
+    pub(crate) filterable: bool,
+    query: String,
 }
 
 impl QuickSelectState {
@@ -163,8 +304,39 @@ impl QuickSelectState {
         Self {
             title,
             choices: choices.into_iter().map(|x| (x.0, x.1.into())).collect(),
+            descriptions: Vec::new(),
+            vertical: false,
+            filterable: false,
+            query: String::new(),
         }
     }
+
+    pub(crate) fn filterable(mut self, v: bool) -> Self {
+        self.filterable = v;
+        self
+    }
+
+    /// Shows the choices that fuzzily match the query, best first, with the top one
+    /// selected.
+    fn narrow(&self, select: &mut QuickSelect) {
+        let mut matches: Vec<_> = (self.choices.iter().enumerate())
+            .filter_map(|(i, choice)| fuzzy_score(&self.query, &choice.1).map(|score| (score, i)))
+            .collect();
+        matches.sort_by_key(|x| x.0);
+        select.choices = matches.iter().map(|x| self.choices[x.1].clone()).collect();
+        select.descriptions = (matches.iter())
+            .filter_map(|x| self.descriptions.get(x.1).cloned())
+            .collect();
+        select.selection = 0;
+        select.query = Some(self.query.clone());
+    }
+
+    /// Shows the choices one per row, each followed by its description.
+    pub(crate) fn vertical(mut self, descriptions: impl IntoIterator<Item = &'static str>) -> Self {
+        self.vertical = true;
+        self.descriptions = descriptions.into_iter().map(Into::into).collect();
+        self
+    }
 }
 
 impl State for QuickSelectState {
@@ -182,21 +354,42 @@ impl State for QuickSelectState {
 
         let input = data.quick_select.as_mut().unwrap();
 
-        if let KeyCode::Char(c) = key.code {
-            if let Some(choice) = input.choices.iter().find(|x| x.0 == c) {
-                return self.pop(Some(choice.1.clone()));
+        match key.code {
+            KeyCode::Char(c) if self.filterable => {
+                self.query.push(c);
+                self.narrow(input);
             }
-        }
-
-        if key.code == KeyCode::Esc {
-            return self.pop(None);
+            KeyCode::Backspace if self.filterable => {
+                self.query.pop();
+                self.narrow(input);
+            }
+            KeyCode::Char(c) => {
+                if let Some(choice) = input.choices.iter().find(|x| x.0 == c) {
+                    return self.pop(Some(choice.1.clone()));
+                }
+            }
+            KeyCode::Up => input.selection = input.selection.saturating_sub(1),
+            KeyCode::Down if input.selection + 1 < input.choices.len() => input.selection += 1,
+            KeyCode::Enter => {
+                if let Some(choice) = input.choices.get(input.selection) {
+                    return self.pop(Some(choice.1.clone()));
+                }
+            }
+            KeyCode::Esc => return self.pop(None),
+            _ => {}
         }
 
         ActResult::Nothing
     }
 
     fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
-        data.quick_select = Some(QuickSelect::new(&self.title).choices(self.choices.clone()));
+        let mut select = QuickSelect::new(&self.title).choices(self.choices.clone());
+        select.descriptions = self.descriptions.clone();
+        select.vertical = self.vertical;
+        if self.filterable {
+            select.query = Some(self.query.clone());
+        }
+        data.quick_select = Some(select);
 
         ActResult::Nothing
     }
@@ -204,3 +397,85 @@ impl State for QuickSelectState {
         data.quick_select = None;
     }
 }
+
+/// Scores how well `query` matches `text` as a case-insensitive subsequence. Lower is
+/// better: matches that start early and have few gaps come first. `None` if it doesn't
+/// match at all.
+pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let mut chars = text.chars().enumerate();
+    let mut score = 0;
+    let mut last = None;
+    for c in query.to_lowercase().chars() {
+        let (i, _) = chars.find(|x| x.1 == c)?;
+        score += match last {
+            Some(last) => i - last - 1,
+            None => i,
+        };
+        last = Some(i);
+    }
+    Some(score)
+}
+
+/// Quick task capture on top of whatever is going on, pushed by the global Ctrl+N.
+/// Dialogs underneath are hidden while it's open and come back untouched.
+#[derive(Default)]
+pub(crate) struct CaptureState {
+    saved_input: Option<QuickInput>,
+    saved_select: Option<QuickSelect>,
+}
+
+impl State for CaptureState {
+    type Action = Action;
+    type Data = Tasker;
+    type Input = Option<String>;
+    type Return = ();
+
+    fn act(
+        &mut self,
+        _data: &mut Self::Data,
+        _action: Self::Action,
+    ) -> ActResult<Self::Action, Self::Data> {
+        panic!("CaptureState shouldn't receive actions");
+    }
+
+    fn resume(
+        &mut self,
+        data: &mut Self::Data,
+        value: Self::Input,
+    ) -> ActResult<Self::Action, Self::Data> {
+        if let Some(text) = value.filter(|text| !text.trim().is_empty()) {
+            data.notify(format!("Captured '{}'", text));
+            data.create_task(text);
+            data.tasklist.refresh(&data.data, &data.filter);
+        }
+
+        self.pop(())
+    }
+
+    fn on_enter(&mut self, data: &mut Self::Data) -> ActResult<Self::Action, Self::Data> {
+        self.saved_input = data.quick_input.take();
+        self.saved_select = data.quick_select.take();
+        self.push(QuickInputState::new("Capture").max_len(data.data.config.title_max_len))
+    }
+
+    fn on_exit(&mut self, data: &mut Self::Data) {
+        data.quick_input = self.saved_input.take();
+        data.quick_select = self.saved_select.take();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("wd", "Work done"), Some(4));
+        assert_eq!(fuzzy_score("work", "Work done"), Some(0));
+        assert!(fuzzy_score("wd", "Work").is_none());
+        assert!(fuzzy_score("dw", "Work done").is_none());
+        assert!(fuzzy_score("do", "Docs") < fuzzy_score("do", "Work done"));
+    }
+}
@@ -1,6 +1,16 @@
-use std::collections::HashMap;
+mod expr;
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub use expr::FilterExpr;
 
 /// A correct-by-construction id for tasks. Can not be constructed for non-existing tasks.
+///
+/// The inner value is private to this module. Raw ids coming from outside the store
+/// (user input, imports) must be resolved with [`TaskStore::lookup`].
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub struct TaskId(u64);
 
@@ -10,15 +20,32 @@ impl TaskId {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
 pub enum Status {
+    #[default]
     Todo,
     Done,
 }
 
-impl Default for Status {
-    fn default() -> Self {
-        Status::Todo
+/// A color for visually grouping tasks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Label {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+}
+
+impl Label {
+    pub const ALL: [Label; 4] = [Label::Red, Label::Yellow, Label::Green, Label::Blue];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Label::Red => "Red",
+            Label::Yellow => "Yellow",
+            Label::Green => "Green",
+            Label::Blue => "Blue",
+        }
     }
 }
 
@@ -30,28 +57,92 @@ pub struct Task {
     pub status: Status,
     pub pomodoros: i32,
     pub links: Vec<TaskId>,
+    /// When the task was last marked done. `None` while it's not done.
+    pub completed_at: Option<SystemTime>,
+    /// Focus time from work timers, including cut-short ones if configured.
+    pub time_spent: Duration,
+    pub label: Option<Label>,
+    /// When to remind about the task. Cleared once the reminder fires.
+    pub reminder: Option<SystemTime>,
 }
 
 impl Task {
-    pub fn toggle_status(&mut self) -> Status {
-        self.status = match self.status {
+    pub fn toggle_status(&mut self, now: SystemTime) -> Status {
+        let status = match self.status {
             Status::Todo => Status::Done,
             Status::Done => Status::Todo,
         };
+        self.set_status(status, now);
         self.status
     }
+
+    /// Sets the status, stamping `completed_at` with `now` or clearing it when it changes.
+    pub fn set_status(&mut self, status: Status, now: SystemTime) {
+        if status == self.status {
+            return;
+        }
+        self.status = status;
+        self.completed_at = match status {
+            Status::Done => Some(now),
+            Status::Todo => None,
+        };
+    }
+}
+
+/// The number of the day `time` falls on in a time zone `utc_offset` seconds east of
+/// UTC, counting from the Unix epoch.
+pub fn day_of(time: SystemTime, utc_offset: i64) -> i64 {
+    const DAY: i64 = 60 * 60 * 24;
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64);
+    (secs + utc_offset).div_euclid(DAY)
+}
+
+/// The first time after `now` at which clocks in a time zone `utc_offset` seconds east
+/// of UTC show `hour:minute`: later today, or else tomorrow.
+pub fn next_time_of_day(now: SystemTime, utc_offset: i64, hour: u32, minute: u32) -> SystemTime {
+    const DAY: i64 = 60 * 60 * 24;
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64);
+    let local = secs + utc_offset;
+    let mut target = local - local.rem_euclid(DAY) + (hour * 60 * 60 + minute * 60) as i64;
+    if target <= local {
+        target += DAY;
+    }
+    UNIX_EPOCH + Duration::from_secs((target - utc_offset).max(0) as u64)
 }
 
 #[derive(Debug, Default)]
 pub struct TaskStore {
     tasks: HashMap<TaskId, Task>,
+    /// Ids of live tasks in creation order, unless rearranged with `move_to_front` or
+    /// `move_to_back`. This is the canonical task order.
+    order: Vec<TaskId>,
+    /// Removed tasks, oldest first. Their ids are dead and never reissued.
+    trash: Vec<Task>,
     id_counter: u64,
+    revision: u64,
 }
 
 impl TaskStore {
-    pub fn new_task(&mut self) -> &mut Task {
+    /// A counter bumped by every mutating method. Consumers can compare revisions to
+    /// find out whether the store has changed since they last looked.
+    ///
+    /// Handing out a `&mut Task` counts as a mutation whether or not the task is changed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn next_id(&mut self) -> TaskId {
         self.id_counter += 1;
-        let id = TaskId(self.id_counter);
+        TaskId(self.id_counter)
+    }
+
+    pub fn new_task(&mut self) -> &mut Task {
+        self.revision += 1;
+        let id = self.next_id();
         let task = Task {
             id,
             title: String::new(),
@@ -59,42 +150,379 @@ impl TaskStore {
             status: Status::default(),
             pomodoros: 0,
             links: Default::default(),
+            completed_at: None,
+            time_spent: Duration::default(),
+            label: None,
+            reminder: None,
         };
         self.tasks.insert(id, task);
+        self.order.push(id);
         self.tasks.get_mut(&id).unwrap()
     }
 
+    /// Resolves a raw numeric id to a `TaskId`, if such a task exists.
+    pub fn lookup(&self, id: u64) -> Option<TaskId> {
+        let id = TaskId(id);
+        if self.tasks.contains_key(&id) {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    /// Finds tasks whose title equals `title`, ignoring case and surrounding whitespace.
+    pub fn find_by_title(&self, title: &str) -> Vec<TaskId> {
+        let title = title.trim().to_lowercase();
+        self.iter()
+            .filter(|task| task.title.trim().to_lowercase() == title)
+            .map(|task| task.id)
+            .collect()
+    }
+
+    pub fn contains(&self, id: TaskId) -> bool {
+        self.tasks.contains_key(&id)
+    }
+
     pub fn get_task(&self, id: TaskId) -> &Task {
         self.tasks.get(&id).expect("Task doesn't exist")
     }
 
     pub fn get_task_mut(&mut self, id: TaskId) -> &mut Task {
+        self.revision += 1;
         self.tasks.get_mut(&id).expect("Task doesn't exist")
     }
+
+    /// Iterates over tasks in the canonical order.
+    pub fn iter(&self) -> impl Iterator<Item = &Task> {
+        self.order.iter().map(move |id| &self.tasks[id])
+    }
+
+    /// Moves a task to the start of the canonical order.
+    pub fn move_to_front(&mut self, id: TaskId) {
+        self.revision += 1;
+        self.order.retain(|x| *x != id);
+        self.order.insert(0, id);
+    }
+
+    /// Moves a task to the end of the canonical order.
+    pub fn move_to_back(&mut self, id: TaskId) {
+        self.revision += 1;
+        self.order.retain(|x| *x != id);
+        self.order.push(id);
+    }
+
+    /// Counts tasks completed on each of the last `days` days up to `now`, oldest first.
+    /// `day` numbers the day a time falls on, e.g. with `day_of`. Only live, currently
+    /// done tasks are counted.
+    pub fn completions_per_day(
+        &self,
+        now: SystemTime,
+        days: usize,
+        day: impl Fn(SystemTime) -> i64,
+    ) -> Vec<u64> {
+        let today = day(now);
+        let mut counts = vec![0; days];
+        for completed in self.iter().filter_map(|task| task.completed_at) {
+            let age = (today - day(completed)).max(0) as usize;
+            if age < days {
+                counts[days - 1 - age] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Renders the live tasks as a Graphviz DOT graph, with links as undirected edges.
+    /// Done tasks are drawn dashed and grey.
+    pub fn export_dot(&self) -> String {
+        let mut dot = String::from("graph tasks {\n");
+        for task in self.iter() {
+            let title = task.title.replace('\\', "\\\\").replace('"', "\\\"");
+            let style = match task.status {
+                Status::Todo => "",
+                Status::Done => ", style=dashed, color=grey, fontcolor=grey",
+            };
+            dot += &format!("    t{} [label=\"{}\"{}];\n", task.id.0, title, style);
+        }
+        for task in self.iter() {
+            // Links go both ways; draw each once.
+            for other in task.links.iter().filter(|other| other.0 > task.id.0) {
+                dot += &format!("    t{} -- t{};\n", task.id.0, other.0);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// Clears reminders that are due at `now` and returns their tasks.
+    pub fn take_due_reminders(&mut self, now: SystemTime) -> Vec<TaskId> {
+        let due: Vec<_> = self
+            .iter()
+            .filter(|task| task.reminder.is_some_and(|time| time <= now))
+            .map(|task| task.id)
+            .collect();
+        for id in &due {
+            self.get_task_mut(*id).reminder = None;
+        }
+        due
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Moves a task to the trash and unlinks it from the tasks it was linked to.
+    pub fn remove_task(&mut self, id: TaskId) {
+        self.revision += 1;
+        let task = self.tasks.remove(&id).expect("Task doesn't exist");
+        self.order.retain(|x| *x != id);
+        for other in &task.links {
+            if let Some(other) = self.tasks.get_mut(other) {
+                other.links.retain(|x| *x != id);
+            }
+        }
+        self.trash.push(task);
+    }
+
+    /// Moves every done task to the trash, returning their ids.
+    pub fn remove_done(&mut self) -> Vec<TaskId> {
+        let done: Vec<_> = self
+            .iter()
+            .filter(|task| task.status == Status::Done)
+            .map(|task| task.id)
+            .collect();
+        for id in &done {
+            self.remove_task(*id);
+        }
+        done
+    }
+
+    pub fn trash(&self) -> &[Task] {
+        &self.trash
+    }
+
+    /// Brings a task back from the trash under a new id. Links to tasks that still
+    /// exist are restored on both ends. Returns `None` if there is no such trash entry.
+    pub fn restore_task(&mut self, index: usize) -> Option<TaskId> {
+        if index >= self.trash.len() {
+            return None;
+        }
+        self.revision += 1;
+        let mut task = self.trash.remove(index);
+        let id = self.next_id();
+        task.id = id;
+        task.links.retain(|x| self.tasks.contains_key(x));
+        for other in &task.links {
+            self.tasks.get_mut(other).unwrap().links.push(id);
+        }
+        self.tasks.insert(id, task);
+        self.order.push(id);
+        Some(id)
+    }
+
+    /// Deletes a trash entry for good. Out-of-range indices are ignored.
+    pub fn delete_from_trash(&mut self, index: usize) {
+        if index < self.trash.len() {
+            self.revision += 1;
+            self.trash.remove(index);
+        }
+    }
+
+    pub fn empty_trash(&mut self) {
+        self.revision += 1;
+        self.trash.clear();
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Filter {
     pub title: String,
     pub status: Option<Status>,
+    pub label: Option<Label>,
+    /// Only match tasks that aren't linked to any other task.
+    pub orphans_only: bool,
+    /// An extra condition on top of the simple criteria above.
+    pub expr: Option<Box<FilterExpr>>,
 }
 
 impl Filter {
-    pub fn apply(&self, store: &TaskStore) -> Vec<TaskId> {
-        let mut results = Vec::new();
+    /// Whether the filter leaves anything out.
+    pub fn is_active(&self) -> bool {
+        !self.title.is_empty()
+            || self.status.is_some()
+            || self.label.is_some()
+            || self.orphans_only
+            || self.expr.is_some()
+    }
 
-        for task in store.tasks.values() {
-            if !task.title.contains(&self.title) {
-                continue;
+    pub fn matches(&self, task: &Task) -> bool {
+        if !task.title.contains(&self.title) {
+            return false;
+        }
+        if let Some(status) = self.status {
+            if task.status != status {
+                return false;
             }
-            if let Some(status) = self.status {
-                if task.status != status {
-                    continue;
-                }
+        }
+        if self.label.is_some() && task.label != self.label {
+            return false;
+        }
+        if self.orphans_only && !task.links.is_empty() {
+            return false;
+        }
+        if let Some(expr) = &self.expr {
+            if !expr.matches(task) {
+                return false;
             }
-            results.push(task.id);
         }
+        true
+    }
 
+    pub fn apply(&self, store: &TaskStore) -> Vec<TaskId> {
+        store
+            .iter()
+            .filter(|task| self.matches(task))
+            .map(|task| task.id)
+            .collect()
+    }
+
+    /// The number of tasks [`apply`](Self::apply) would return, without collecting them.
+    pub fn count(&self, store: &TaskStore) -> usize {
+        store.iter().filter(|task| self.matches(task)).count()
+    }
+
+    /// Like [`apply`](Self::apply), but ranks exact title matches first and prefix
+    /// matches second, ahead of other substring matches. Ties keep `apply` order.
+    pub fn apply_ranked(&self, store: &TaskStore) -> Vec<TaskId> {
+        let mut results = self.apply(store);
+        results.sort_by_key(|id| {
+            let title = store.get_task(*id).title.as_str();
+            if title == self.title {
+                0
+            } else if title.starts_with(self.title.as_str()) {
+                1
+            } else {
+                2
+            }
+        });
         results
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_completions_per_day() {
+        const DAY: u64 = 60 * 60 * 24;
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY + 60);
+        let mut store = TaskStore::default();
+        for (days_ago, seconds) in [(0, 0), (0, 30), (1, 0), (3, 0), (20, 0)] {
+            let completed = UNIX_EPOCH + Duration::from_secs((100 - days_ago) * DAY + seconds);
+            store.new_task().set_status(Status::Done, completed);
+        }
+        store.new_task();
+
+        let utc = |time| day_of(time, 0);
+        assert_eq!(store.completions_per_day(now, 5, utc), vec![0, 1, 0, 1, 2]);
+
+        // A minute west of UTC, every completion here falls a day earlier than today.
+        let west = |time| day_of(time, -60);
+        assert_eq!(store.completions_per_day(now, 5, west), vec![1, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_next_time_of_day() {
+        const HOUR: u64 = 60 * 60;
+        // 10:00 UTC, 12:00 two hours east.
+        let now = UNIX_EPOCH + Duration::from_secs(100 * 24 * HOUR + 10 * HOUR);
+        let at = |offset, hour, minute| next_time_of_day(now, offset, hour, minute);
+
+        assert_eq!(at(0, 15, 0), now + Duration::from_secs(5 * HOUR));
+        assert_eq!(at(2 * 60 * 60, 15, 0), now + Duration::from_secs(3 * HOUR));
+        assert_eq!(at(0, 10, 0), now + Duration::from_secs(24 * HOUR));
+        assert_eq!(at(0, 9, 30), now + Duration::from_secs(23 * HOUR + 30 * 60));
+    }
+
+    #[test]
+    fn test_revision() {
+        let mut store = TaskStore::default();
+        let mut last = store.revision();
+        let mut bumped = |store: &TaskStore| {
+            let bumped = store.revision() > last;
+            last = store.revision();
+            bumped
+        };
+
+        let a = store.new_task().id;
+        assert!(bumped(&store));
+        let b = store.new_task().id;
+        assert!(bumped(&store));
+        store.get_task_mut(a).reminder = Some(UNIX_EPOCH);
+        assert!(bumped(&store));
+        store.move_to_front(b);
+        assert!(bumped(&store));
+        store.move_to_back(b);
+        assert!(bumped(&store));
+        assert_eq!(store.take_due_reminders(UNIX_EPOCH), vec![a]);
+        assert!(bumped(&store));
+        store.get_task_mut(b).set_status(Status::Done, UNIX_EPOCH);
+        assert!(bumped(&store));
+        assert_eq!(store.remove_done(), vec![b]);
+        assert!(bumped(&store));
+        store.remove_task(a);
+        assert!(bumped(&store));
+        let restored = store.restore_task(0).unwrap();
+        assert!(bumped(&store));
+        store.delete_from_trash(0);
+        assert!(bumped(&store));
+        store.empty_trash();
+        assert!(bumped(&store));
+
+        // Reading doesn't count.
+        store.get_task(restored);
+        store.iter().count();
+        assert!(!bumped(&store));
+    }
+
+    #[test]
+    fn test_trash_index_out_of_range() {
+        let mut store = TaskStore::default();
+        let id = store.new_task().id;
+        store.remove_task(id);
+        store.empty_trash();
+        let revision = store.revision();
+
+        assert_eq!(store.restore_task(0), None);
+        store.delete_from_trash(0);
+        assert_eq!(store.revision(), revision);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_export_dot() {
+        let mut store = TaskStore::default();
+        let a = store.new_task();
+        a.title = "Say \"hi\"".into();
+        let a = a.id;
+        let b = store.new_task();
+        b.title = "Done".into();
+        b.set_status(Status::Done, UNIX_EPOCH);
+        b.links.push(a);
+        let b = b.id;
+        store.get_task_mut(a).links.push(b);
+
+        assert_eq!(
+            store.export_dot(),
+            "graph tasks {\n\
+             \x20   t1 [label=\"Say \\\"hi\\\"\"];\n\
+             \x20   t2 [label=\"Done\", style=dashed, color=grey, fontcolor=grey];\n\
+             \x20   t1 -- t2;\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt;
+
+use super::{Filter, Status, Task};
+
+/// A boolean combination of filters, e.g. `done or (todo and not "later")`.
+#[derive(Debug, Clone)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Leaf(Filter),
+}
+
+impl FilterExpr {
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            FilterExpr::And(a, b) => a.matches(task) && b.matches(task),
+            FilterExpr::Or(a, b) => a.matches(task) || b.matches(task),
+            FilterExpr::Not(a) => !a.matches(task),
+            FilterExpr::Leaf(filter) => filter.matches(task),
+        }
+    }
+
+    /// Parses an expression. `todo` and `done` match a status and any other word or
+    /// quoted string matches titles containing it. `not` binds tightest, then `and`,
+    /// then `or`. Parentheses group.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected {}", token)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(word) => write!(f, "\"{}\"", word),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unclosed quote".into()),
+                    }
+                }
+                tokens.push(Token::Quoted(word));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.and()?;
+        while self.at_keyword("or") {
+            self.pos += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.not()?;
+        while self.at_keyword("and") {
+            self.pos += 1;
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<FilterExpr, String> {
+        if self.at_keyword("not") {
+            self.pos += 1;
+            return Ok(FilterExpr::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<FilterExpr, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of filter".to_owned())?;
+        self.pos += 1;
+
+        let status = |status| {
+            FilterExpr::Leaf(Filter {
+                status: Some(status),
+                ..Filter::default()
+            })
+        };
+        match token {
+            Token::Open => {
+                let expr = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("Missing ')'".into()),
+                }
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("todo") => Ok(status(Status::Todo)),
+            Token::Word(word) if word.eq_ignore_ascii_case("done") => Ok(status(Status::Done)),
+            Token::Word(word)
+                if ["and", "or", "not"]
+                    .iter()
+                    .any(|keyword| word.eq_ignore_ascii_case(keyword)) =>
+            {
+                Err(format!("Unexpected '{}'", word))
+            }
+            Token::Word(title) | Token::Quoted(title) => Ok(FilterExpr::Leaf(Filter {
+                title,
+                ..Filter::default()
+            })),
+            Token::Close => Err(format!("Unexpected {}", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::task::TaskStore;
+
+    fn store() -> TaskStore {
+        let mut store = TaskStore::default();
+        for (title, status) in [
+            ("a", Status::Todo),
+            ("b", Status::Done),
+            ("c", Status::Todo),
+            ("a b", Status::Done),
+        ] {
+            let task = store.new_task();
+            task.title = title.into();
+            task.set_status(status, std::time::UNIX_EPOCH);
+        }
+        store
+    }
+
+    fn titles(store: &TaskStore, text: &str) -> Vec<String> {
+        let expr = FilterExpr::parse(text).unwrap();
+        store
+            .iter()
+            .filter(|task| expr.matches(task))
+            .map(|task| task.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_expr_precedence() {
+        let store = store();
+        // `and` binds tighter than `or`.
+        assert_eq!(titles(&store, "c or a and done"), ["c", "a b"]);
+        assert_eq!(titles(&store, "(c or a) and done"), ["a b"]);
+        assert_eq!(titles(&store, "done OR c"), ["b", "c", "a b"]);
+    }
+
+    #[test]
+    fn test_filter_expr_negation() {
+        let store = store();
+        assert_eq!(titles(&store, "not done"), ["a", "c"]);
+        // `not` binds tighter than `and`.
+        assert_eq!(titles(&store, "not a and todo"), ["c"]);
+        assert_eq!(titles(&store, "not (a and todo)"), ["b", "c", "a b"]);
+        assert_eq!(titles(&store, "not not \"a b\""), ["a b"]);
+    }
+
+    #[test]
+    fn test_filter_expr_errors() {
+        assert!(FilterExpr::parse("").is_err());
+        assert!(FilterExpr::parse("a and").is_err());
+        assert!(FilterExpr::parse("(a or b").is_err());
+        assert!(FilterExpr::parse("a b").is_err());
+        assert!(FilterExpr::parse("\"a").is_err());
+    }
+}
@@ -0,0 +1,1227 @@
+//! End-to-end tests that drive the automaton with key presses, the same way the event
+//! loop does, and check the resulting application state.
+
+use std::{
+    rc::Rc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::style::Modifier;
+
+use crate::{
+    automaton::Machine,
+    clock::{Clock, MockClock},
+    hooks::TimerHook,
+    state::NormalState,
+    task::{Filter, Label, Status, TaskId},
+    Action, Tasker,
+};
+
+pub(crate) struct Harness {
+    pub(crate) tasker: Tasker,
+    pub(crate) machine: Machine<Action, Tasker>,
+}
+
+impl Harness {
+    pub(crate) fn new() -> Self {
+        Self {
+            tasker: Tasker::default(),
+            machine: Machine::new(NormalState),
+        }
+    }
+
+    pub(crate) fn key(&mut self, code: KeyCode) -> &mut Self {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    pub(crate) fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        let key = KeyEvent::new(code, modifiers);
+        crate::dispatch(&mut self.machine, &mut self.tasker, Action::Key(key));
+        self
+    }
+
+    /// Like `key_with`, but goes through the macro recorder first.
+    pub(crate) fn press(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        let key = KeyEvent::new(code, modifiers);
+        crate::handle_key(&mut self.machine, &mut self.tasker, key);
+        self
+    }
+
+    /// Presses a key for every character of `text`.
+    pub(crate) fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.key(KeyCode::Char(c));
+        }
+        self
+    }
+
+    pub(crate) fn create_task(&mut self, title: &str) -> TaskId {
+        self.key(KeyCode::Char('n'))
+            .type_text(title)
+            .key(KeyCode::Enter);
+        self.tasker.tasklist.selection().unwrap()
+    }
+}
+
+#[test]
+fn test_create_task() {
+    let mut h = Harness::new();
+    let id = h.create_task("Write tests");
+
+    assert_eq!(h.tasker.data.store.len(), 1);
+    assert_eq!(h.tasker.data.store.get_task(id).title, "Write tests");
+    assert!(h.tasker.quick_input.is_none());
+}
+
+#[test]
+fn test_cancel_create_task() {
+    let mut h = Harness::new();
+    h.key(KeyCode::Char('n'))
+        .type_text("Nope")
+        .key(KeyCode::Esc);
+
+    assert!(h.tasker.data.store.is_empty());
+    assert!(h.tasker.quick_input.is_none());
+}
+
+#[test]
+fn test_set_description() {
+    let mut h = Harness::new();
+    let id = h.create_task("Task");
+    h.key(KeyCode::Char('e'))
+        .type_text("Some details")
+        .key(KeyCode::Enter);
+
+    assert_eq!(h.tasker.data.store.get_task(id).description, "Some details");
+}
+
+#[test]
+fn test_link_tasks() {
+    let mut h = Harness::new();
+    let first = h.create_task("First");
+    let second = h.create_task("Second");
+
+    h.key(KeyCode::Up).key(KeyCode::Enter);
+    assert_eq!(h.tasker.last_task, Some(first));
+
+    h.key(KeyCode::Char('l'))
+        .type_text("Second")
+        .key(KeyCode::Enter);
+
+    let store = &h.tasker.data.store;
+    assert_eq!(store.get_task(first).links, vec![second]);
+    assert_eq!(store.get_task(second).links, vec![first]);
+    assert!(h.tasker.search.is_none());
+}
+
+#[test]
+fn test_move_to_top_and_bottom() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    let b = h.create_task("b");
+    let c = h.create_task("c");
+
+    h.key(KeyCode::Char('<'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![c, a, b]);
+    assert_eq!(h.tasker.tasklist.selection(), Some(c));
+
+    h.key(KeyCode::Down).key(KeyCode::Char('>'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![c, b, a]);
+    assert_eq!(h.tasker.tasklist.selection(), Some(a));
+}
+
+#[test]
+fn test_save_and_load_filter() {
+    let mut h = Harness::new();
+    let todo = h.create_task("Open");
+    let done = h.create_task("Closed");
+    h.key(KeyCode::Char(' '));
+
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('d'));
+    h.key(KeyCode::Char('f'))
+        .key(KeyCode::Char('s'))
+        .type_text("todos")
+        .key(KeyCode::Enter);
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('c'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![todo, done]);
+
+    h.key(KeyCode::Char('f'))
+        .key(KeyCode::Char('l'))
+        .key(KeyCode::Char('1'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![todo]);
+    assert!(h.tasker.quick_select.is_none());
+}
+
+#[test]
+fn test_preview_marks_long_description() {
+    let mut h = Harness::new();
+    let id = h.create_task("Task");
+    h.tasker.data.store.get_task_mut(id).description = "word ".repeat(200);
+    assert!(h.tasker.render_to_string().contains("… (more)"));
+
+    h.tasker.data.store.get_task_mut(id).description = "short".into();
+    assert!(!h.tasker.render_to_string().contains("(more)"));
+}
+
+#[test]
+fn test_duplicate_title_warning() {
+    let mut h = Harness::new();
+    h.tasker.data.config.warn_duplicate_titles = true;
+    let first = h.create_task("Groceries");
+
+    h.key(KeyCode::Char('n'))
+        .type_text(" groceries")
+        .key(KeyCode::Enter);
+    assert!(h.tasker.quick_select.is_some());
+    h.key(KeyCode::Char('c'));
+    assert_eq!(h.tasker.data.store.len(), 2);
+
+    h.key(KeyCode::Char('n'))
+        .type_text("GROCERIES")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Char('o'));
+    assert_eq!(h.tasker.data.store.len(), 2);
+    assert_eq!(h.tasker.last_task, Some(first));
+}
+
+#[test]
+fn test_split_task() {
+    let mut h = Harness::new();
+    let id = h.create_task("Big task");
+    h.tasker.data.store.get_task_mut(id).description = "First\n\n  Second \n".into();
+
+    h.key(KeyCode::Enter)
+        .key(KeyCode::Char('s'))
+        .key(KeyCode::Char('c'));
+
+    let store = &h.tasker.data.store;
+    let links = &store.get_task(id).links;
+    let titles: Vec<_> = links
+        .iter()
+        .map(|x| store.get_task(*x).title.as_str())
+        .collect();
+    assert_eq!(titles, ["First", "Second"]);
+    assert!(store.get_task(links[0]).links.contains(&id));
+    assert!(store.get_task(id).description.is_empty());
+    assert_eq!(h.tasker.tasklist.tasks.len(), 3);
+}
+
+#[test]
+fn test_orphans_filter() {
+    let mut h = Harness::new();
+    let first = h.create_task("First");
+    h.create_task("Second");
+    let lonely = h.create_task("Lonely");
+
+    h.key(KeyCode::Up).key(KeyCode::Up).key(KeyCode::Enter);
+    h.key(KeyCode::Char('l'))
+        .type_text("Second")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc);
+    assert_eq!(h.tasker.data.store.get_task(first).links.len(), 1);
+
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('o'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![lonely]);
+}
+
+#[test]
+fn test_board_columns() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    let b = h.create_task("b");
+
+    h.key(KeyCode::Char('b')).key(KeyCode::Char(' '));
+    let board = match &h.tasker.pane {
+        crate::Pane::Board(board) => board,
+        _ => panic!("Board not open"),
+    };
+    assert_eq!(board.columns[0].1.tasks, vec![b]);
+    assert_eq!(board.columns[1].1.tasks, vec![a]);
+
+    let screen = h.tasker.render_to_string();
+    assert!(screen.contains("Todo") && screen.contains("Done"));
+}
+
+#[test]
+fn test_board_move_between_columns() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    let b = h.create_task("b");
+
+    h.key(KeyCode::Char('b'))
+        .key(KeyCode::Down)
+        .key_with(KeyCode::Right, KeyModifiers::SHIFT);
+    assert_eq!(h.tasker.data.store.get_task(b).status, Status::Done);
+    h.key(KeyCode::Left)
+        .key_with(KeyCode::Right, KeyModifiers::SHIFT);
+    assert_eq!(h.tasker.data.store.get_task(a).status, Status::Done);
+
+    let board = match &h.tasker.pane {
+        crate::Pane::Board(board) => board,
+        _ => panic!("Board not open"),
+    };
+    assert_eq!(board.column, 1);
+    assert_eq!(board.selection(), Some(a));
+
+    h.key_with(KeyCode::Left, KeyModifiers::SHIFT);
+    assert_eq!(h.tasker.data.store.get_task(a).status, Status::Todo);
+}
+
+#[test]
+fn test_capture_keeps_dialog() {
+    let mut h = Harness::new();
+    let id = h.create_task("Task");
+
+    h.key(KeyCode::Char('e')).type_text("Half a");
+    h.key_with(KeyCode::Char('n'), KeyModifiers::CONTROL)
+        .type_text("Idea")
+        .key(KeyCode::Enter);
+    assert_eq!(h.tasker.data.store.len(), 2);
+    assert_eq!(h.tasker.quick_input.as_ref().unwrap().text, "Half a");
+
+    h.type_text(" sentence").key(KeyCode::Enter);
+    assert_eq!(
+        h.tasker.data.store.get_task(id).description,
+        "Half a sentence"
+    );
+}
+
+#[test]
+fn test_link_list_done_summary() {
+    let mut h = Harness::new();
+    let first = h.create_task("First");
+    let second = h.create_task("Second");
+    h.create_task("Third");
+
+    h.key(KeyCode::Up).key(KeyCode::Up).key(KeyCode::Enter);
+    for title in ["Second", "Third"] {
+        h.key(KeyCode::Char('l'))
+            .type_text(title)
+            .key(KeyCode::Enter);
+    }
+    h.tasker
+        .data
+        .store
+        .get_task_mut(second)
+        .toggle_status(UNIX_EPOCH);
+
+    assert_eq!(h.tasker.last_task, Some(first));
+    assert!(h
+        .tasker
+        .render_to_string()
+        .contains("Linked tasks — 1/2 done"));
+}
+
+#[test]
+fn test_link_list_follows_store() {
+    let mut h = Harness::new();
+    let first = h.create_task("First");
+    let second = h.create_task("Second");
+
+    h.key(KeyCode::Up).key(KeyCode::Enter);
+    h.key(KeyCode::Char('l'))
+        .type_text("Second")
+        .key(KeyCode::Enter);
+    // Links are made from the other end, behind the view's back.
+    let third = h.tasker.create_task("Third".into());
+    h.tasker.data.store.get_task_mut(first).links.push(third);
+    h.tasker.data.store.get_task_mut(third).links.push(first);
+
+    h.key(KeyCode::Down).key(KeyCode::Enter);
+    assert_eq!(h.tasker.last_task, Some(third));
+    assert_eq!(
+        h.tasker.data.store.get_task(first).links,
+        vec![second, third]
+    );
+}
+
+#[test]
+fn test_toggle_in_status_filter() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    let b = h.create_task("b");
+    let c = h.create_task("c");
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('d'));
+
+    h.key(KeyCode::Down).key(KeyCode::Char(' '));
+    assert_eq!(h.tasker.tasklist.tasks, vec![a, c]);
+    assert_eq!(h.tasker.tasklist.selection(), Some(c));
+    assert_eq!(h.tasker.data.store.get_task(b).status, Status::Done);
+}
+
+#[test]
+fn test_filter_follows_toggle_in_other_views() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    let b = h.create_task("b");
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('d'));
+
+    // Toggle in the task view, then come back to the list.
+    h.key(KeyCode::Enter)
+        .key(KeyCode::Char(' '))
+        .key(KeyCode::Esc);
+    assert_eq!(h.tasker.tasklist.tasks, vec![b]);
+
+    // And in the board.
+    h.key(KeyCode::Char('b'))
+        .key(KeyCode::Right)
+        .key(KeyCode::Char(' '))
+        .key(KeyCode::Esc);
+    assert_eq!(h.tasker.tasklist.tasks, vec![a, b]);
+}
+
+#[test]
+fn test_board_tab_cycles_columns() {
+    let mut h = Harness::new();
+    h.key(KeyCode::Char('b'));
+    let column = |h: &Harness| match &h.tasker.pane {
+        crate::Pane::Board(board) => board.column,
+        _ => panic!("Board not open"),
+    };
+
+    h.key_with(KeyCode::BackTab, KeyModifiers::SHIFT);
+    assert_eq!(column(&h), 1);
+    h.key(KeyCode::Tab);
+    assert_eq!(column(&h), 0);
+}
+
+#[test]
+fn test_go_to_id_validation() {
+    let mut h = Harness::new();
+    let id = h.create_task("Target");
+
+    h.key(KeyCode::Char('g')).type_text("9");
+    let feedback = h.tasker.quick_input.as_ref().unwrap().feedback.clone();
+    assert_eq!(feedback, Some(Err("no such task".into())));
+    h.key(KeyCode::Enter);
+    assert!(h.tasker.quick_input.is_some());
+
+    h.key(KeyCode::Backspace).type_text("1");
+    let feedback = h.tasker.quick_input.as_ref().unwrap().feedback.clone();
+    assert_eq!(feedback, Some(Ok("Target".into())));
+    h.key(KeyCode::Enter);
+    assert_eq!(h.tasker.last_task, Some(id));
+
+    // Empty input closes the dialog without a message.
+    h.key(KeyCode::Esc)
+        .key(KeyCode::Char('g'))
+        .key(KeyCode::Enter);
+    assert!(h.tasker.quick_input.is_none());
+    assert_eq!(h.tasker.notification, None);
+}
+
+#[test]
+fn test_title_max_len() {
+    let mut h = Harness::new();
+    h.tasker.data.config.title_max_len = 5;
+
+    h.key(KeyCode::Char('n')).type_text("héllo world");
+    assert!(h.tasker.render_to_string().contains("Title: héllo  5/5"));
+    h.key(KeyCode::Enter);
+
+    let id = h.tasker.tasklist.selection().unwrap();
+    assert_eq!(h.tasker.data.store.get_task(id).title, "héllo");
+}
+
+#[test]
+fn test_filtered_progress() {
+    let mut h = Harness::new();
+    for title in ["x 1", "x 2", "x 3", "y"] {
+        h.create_task(title);
+    }
+    h.key(KeyCode::Up).key(KeyCode::Char(' '));
+    assert!(h.tasker.render_to_string().contains(" Tasks "));
+
+    h.key(KeyCode::Char('f'))
+        .key(KeyCode::Char('t'))
+        .type_text("x")
+        .key(KeyCode::Enter);
+    assert!(h
+        .tasker
+        .render_to_string()
+        .contains("Tasks — 33% complete (1/3)"));
+}
+
+#[test]
+fn test_clear_timer_confirmation() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.create_task("Focus");
+
+    let start = |h: &mut Harness| {
+        h.key(KeyCode::Char('p')).key(KeyCode::Char('p'));
+    };
+    let clear = |h: &mut Harness| {
+        h.key(KeyCode::Char('p')).key(KeyCode::Char('c'));
+    };
+
+    start(&mut h);
+    clock.advance(Duration::from_secs(60 * 2));
+    clear(&mut h);
+    assert!(h.tasker.timer.is_none());
+
+    start(&mut h);
+    clock.advance(Duration::from_secs(60 * 18 + 30));
+    clear(&mut h);
+    assert!(h.tasker.timer.is_some());
+    assert_eq!(
+        h.tasker.quick_select.as_ref().unwrap().title,
+        "Discard 18 min of focus?"
+    );
+    h.key(KeyCode::Char('n'));
+    assert!(h.tasker.timer.is_some());
+
+    clear(&mut h);
+    h.key(KeyCode::Char('y'));
+    assert!(h.tasker.timer.is_none());
+}
+
+#[test]
+fn test_credit_partial_work() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    let id = h.create_task("Focus");
+
+    let work = |h: &mut Harness, minutes: u64| {
+        h.key(KeyCode::Char('p')).key(KeyCode::Char('p'));
+        clock.advance(Duration::from_secs(60 * minutes));
+        h.key(KeyCode::Char('p')).key(KeyCode::Char('c'));
+    };
+
+    work(&mut h, 3);
+    assert_eq!(
+        h.tasker.data.store.get_task(id).time_spent,
+        Duration::default()
+    );
+
+    h.tasker.data.config.credit_partial_work = true;
+    work(&mut h, 3);
+    work(&mut h, 10);
+    h.key(KeyCode::Char('y'));
+    let task = h.tasker.data.store.get_task(id);
+    assert_eq!(task.time_spent, Duration::from_secs(60 * 13));
+    assert_eq!(task.pomodoros, 0);
+}
+
+#[test]
+fn test_focus_mode() {
+    let mut h = Harness::new();
+    h.create_task("Other");
+    let id = h.create_task("Deep work");
+    h.tasker.data.store.get_task_mut(id).description = "Only this".into();
+
+    h.key(KeyCode::Char('z'));
+    let screen = h.tasker.render_to_string();
+    assert!(screen.contains("Deep work") && screen.contains("Only this"));
+    assert!(!screen.contains("Other"));
+
+    h.key(KeyCode::Char('x'));
+    assert!(h.tasker.focus.is_none());
+    assert!(h.tasker.render_to_string().contains("Other"));
+}
+
+#[test]
+fn test_double_press_quit() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.press_quit();
+    assert!(h.tasker.quit_requested);
+
+    h.tasker.quit_requested = false;
+    h.tasker.data.config.quit_double_press = true;
+    h.tasker.press_quit();
+    assert!(!h.tasker.quit_requested);
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Press again to quit")
+    );
+    clock.advance(Duration::from_secs(2));
+    h.tasker.press_quit();
+    assert!(!h.tasker.quit_requested);
+    clock.advance(Duration::from_millis(500));
+    h.tasker.press_quit();
+    assert!(h.tasker.quit_requested);
+}
+
+#[test]
+fn test_double_press_quit_with_q() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.quit_double_press = true;
+
+    h.key(KeyCode::Char('q'));
+    assert!(!h.tasker.quit_requested);
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Press again to quit")
+    );
+    clock.advance(Duration::from_millis(500));
+    h.key(KeyCode::Char('q'));
+    assert!(h.tasker.quit_requested);
+}
+
+#[test]
+fn test_q_quits_only_from_list() {
+    let mut h = Harness::new();
+    h.key(KeyCode::Char('n')).type_text("quiet");
+    assert!(!h.tasker.quit_requested);
+
+    h.key(KeyCode::Enter).key(KeyCode::Char('q'));
+    assert!(h.tasker.quit_requested);
+}
+
+#[test]
+fn test_find_and_replace() {
+    let mut h = Harness::new();
+    let a = h.create_task("Apollo: launch");
+    let b = h.create_task("Unrelated");
+    h.tasker.data.store.get_task_mut(b).description = "see Apollo notes".into();
+    let c = h.create_task("Apollo done");
+    h.key(KeyCode::Char(' '));
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('d'));
+
+    h.key(KeyCode::Char('R'))
+        .type_text("Apollo")
+        .key(KeyCode::Enter)
+        .type_text("Artemis")
+        .key(KeyCode::Enter);
+
+    let store = &h.tasker.data.store;
+    assert_eq!(store.get_task(a).title, "Artemis: launch");
+    assert_eq!(store.get_task(b).description, "see Artemis notes");
+    // Filtered out, so left alone.
+    assert_eq!(store.get_task(c).title, "Apollo done");
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Replaced 'Apollo' in 2 tasks")
+    );
+}
+
+#[test]
+fn test_completions_sparkline() {
+    let mut h = Harness::new();
+    h.create_task("a");
+    assert!(!h.tasker.render_to_string().contains('█'));
+
+    h.key(KeyCode::Char(' '));
+    let screen = h.tasker.render_to_string();
+    let status_line = screen.lines().last().unwrap();
+    assert!(status_line.ends_with('█'));
+}
+
+#[test]
+fn test_color_labels() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    h.create_task("b");
+
+    h.key(KeyCode::Up)
+        .key(KeyCode::Char('c'))
+        .key(KeyCode::Char('g'));
+    assert_eq!(h.tasker.data.store.get_task(a).label, Some(Label::Green));
+
+    h.tasker.data.colors = false;
+    assert!(h.tasker.render_to_string().contains("[G] TODO a"));
+
+    h.key(KeyCode::Char('f'))
+        .key(KeyCode::Char('L'))
+        .key(KeyCode::Char('g'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![a]);
+}
+
+#[test]
+fn test_find_in_list() {
+    let mut h = Harness::new();
+    let a = h.create_task("apple pie");
+    h.create_task("banana");
+    let c = h.create_task("Apple juice");
+    h.key(KeyCode::Up).key(KeyCode::Up);
+
+    h.key(KeyCode::Char('/')).type_text("app");
+    assert_eq!(h.tasker.tasklist.selection(), Some(a));
+    h.key(KeyCode::Enter).key(KeyCode::Char('n'));
+    assert_eq!(h.tasker.tasklist.selection(), Some(c));
+    h.key(KeyCode::Char('n'));
+    assert_eq!(h.tasker.tasklist.selection(), Some(a));
+    h.key(KeyCode::Char('N'));
+    assert_eq!(h.tasker.tasklist.selection(), Some(c));
+    assert_eq!(h.tasker.tasklist.tasks.len(), 3);
+
+    // Any other key leaves find mode, so 'n' creates tasks again.
+    h.key(KeyCode::Esc).key(KeyCode::Char('n'));
+    assert!(h.tasker.quick_input.is_some());
+    assert_eq!(h.tasker.tasklist.find, None);
+}
+
+#[test]
+fn test_find_underline_respects_no_color() {
+    let mut h = Harness::new();
+    h.create_task("apple");
+    h.key(KeyCode::Char('/')).type_text("app");
+    let underlined = |h: &mut Harness| {
+        let buffer = h.tasker.render();
+        buffer
+            .content
+            .iter()
+            .any(|cell| cell.modifier.contains(Modifier::UNDERLINED))
+    };
+
+    assert!(underlined(&mut h));
+    h.tasker.data.config.no_color = true;
+    assert!(!underlined(&mut h));
+}
+
+#[test]
+fn test_number_input() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.create_task("a");
+
+    h.key(KeyCode::Char('p')).key(KeyCode::Char('m'));
+    h.type_text("1x5");
+    assert_eq!(h.tasker.quick_input.as_ref().unwrap().text, "15");
+    h.key(KeyCode::Enter);
+    let remaining = h.tasker.timer.as_ref().unwrap().remaining(clock.now());
+    assert_eq!(remaining, Some(Duration::from_secs(60 * 15)));
+
+    h.key(KeyCode::Char('p')).key(KeyCode::Char('m'));
+    h.type_text("500").key(KeyCode::Enter);
+    let remaining = h.tasker.timer.as_ref().unwrap().remaining(clock.now());
+    assert_eq!(remaining, Some(Duration::from_secs(60 * 120)));
+}
+
+#[test]
+fn test_start_timer_with_hook() {
+    let mut h = Harness::new();
+    h.create_task("a");
+    h.tasker.data.config.timer_hooks = vec![
+        ("notify".into(), TimerHook::Command("true".into())),
+        ("log".into(), TimerHook::Log("work.log".into())),
+    ];
+
+    h.key(KeyCode::Char('p'))
+        .key(KeyCode::Char('h'))
+        .key(KeyCode::Char('2'));
+    let timer = h.tasker.timer.as_ref().unwrap();
+    assert_eq!(timer.title, "WORK");
+    assert!(matches!(&timer.hook, Some((name, TimerHook::Log(_))) if name == "log"));
+}
+
+#[test]
+fn test_start_timer_with_typed_hook() {
+    let mut h = Harness::new();
+    h.create_task("a");
+
+    h.key(KeyCode::Char('p'))
+        .key(KeyCode::Char('h'))
+        .key(KeyCode::Char('c'))
+        .type_text("notify-send done")
+        .key(KeyCode::Enter);
+    let timer = h.tasker.timer.as_ref().unwrap();
+    assert!(matches!(
+        &timer.hook,
+        Some((_, TimerHook::Command(command))) if command == "notify-send done"
+    ));
+
+    h.key(KeyCode::Char('p'))
+        .key(KeyCode::Char('h'))
+        .key(KeyCode::Char('l'))
+        .type_text("work.log")
+        .key(KeyCode::Enter);
+    let timer = h.tasker.timer.as_ref().unwrap();
+    assert!(matches!(&timer.hook, Some((name, TimerHook::Log(_))) if name == "work.log"));
+}
+
+#[test]
+fn test_daily_pomodoro_goal() {
+    let mut h = Harness::new();
+    assert!(!h.tasker.render_to_string().contains("today"));
+
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.daily_pomodoro_goal = 3;
+    let now = clock.wall_now();
+    h.tasker.data.pomodoro_log = vec![now - Duration::from_secs(60 * 60 * 48), now, now];
+    assert!(h.tasker.render_to_string().contains("2/3 today"));
+
+    h.tasker.data.pomodoro_log.push(now);
+    assert!(h.tasker.render_to_string().contains("✓ 3/3 today"));
+}
+
+#[test]
+fn test_pomodoro_goal_resets_at_local_midnight() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.daily_pomodoro_goal = 8;
+
+    // Local midnight two hours east of UTC is at 22:00 UTC, so these are on the same
+    // UTC day but on different local days.
+    const DAY: u64 = 60 * 60 * 24;
+    let local_midnight = UNIX_EPOCH + Duration::from_secs(100 * DAY - 2 * 60 * 60);
+    clock.set_utc_offset(2 * 60 * 60);
+    clock.set_wall(local_midnight + Duration::from_secs(60));
+    h.tasker.data.pomodoro_log = vec![local_midnight - Duration::from_secs(60)];
+    assert!(h.tasker.render_to_string().contains("0/8 today"));
+
+    clock.set_wall(local_midnight - Duration::from_secs(30));
+    assert!(h.tasker.render_to_string().contains("1/8 today"));
+}
+
+#[test]
+fn test_compact_preview() {
+    let mut h = Harness::new();
+    h.create_task("Write docs");
+    h.key(KeyCode::Char('e')).type_text("First line");
+    h.key(KeyCode::Enter);
+
+    h.tasker.data.config.preview_rows = 2;
+    let screen = h.tasker.render_to_string();
+    // Once in the list, once in the preview.
+    assert_eq!(screen.matches("TODO Write docs").count(), 2);
+    assert!(screen.contains("First line"));
+    assert!(!screen.contains("Status:"));
+
+    h.tasker.data.config.preview_rows = 5;
+    assert!(h.tasker.render_to_string().contains("Status: TODO"));
+}
+
+#[test]
+fn test_vertical_quick_select() {
+    let mut h = Harness::new();
+    h.create_task("a");
+    h.create_task("b");
+    h.key(KeyCode::Char(' '));
+
+    h.key(KeyCode::Char('f'));
+    let screen = h.tasker.render_to_string();
+    assert!(screen.contains("[o] Orphans  only tasks without links"));
+
+    // Arrow keys and Enter pick the same as the hotkey 'D'.
+    h.key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Up)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    assert_eq!(h.tasker.filter.status, Some(Status::Done));
+    assert!(h.tasker.quick_select.is_none());
+}
+
+#[test]
+fn test_enter_in_horizontal_quick_select() {
+    let mut h = Harness::new();
+    let id = h.create_task("a");
+
+    // The color menu fits on one line. Enter picks the highlighted choice there too.
+    h.key(KeyCode::Char('c'));
+    let screen = h.tasker.render_to_string();
+    assert!(screen.lines().last().unwrap().contains("[r] Red"));
+    h.key(KeyCode::Enter);
+    assert_eq!(h.tasker.data.store.get_task(id).label, Some(Label::Red));
+
+    h.key(KeyCode::Char('c'))
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    assert_eq!(h.tasker.data.store.get_task(id).label, Some(Label::Yellow));
+}
+
+#[test]
+fn test_fuzzy_load_filter() {
+    let mut h = Harness::new();
+    let todo = h.create_task("a");
+    let done = h.create_task("b");
+    h.key(KeyCode::Char(' '));
+    for i in 0..10 {
+        let filter = Filter::default();
+        h.tasker
+            .saved_filters
+            .insert(format!("filter {}", i), filter);
+    }
+    let done_only = Filter {
+        status: Some(Status::Done),
+        ..Filter::default()
+    };
+    h.tasker.saved_filters.insert("work done".into(), done_only);
+
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('l'));
+    h.type_text("wkdn");
+    let select = h.tasker.quick_select.as_ref().unwrap();
+    assert_eq!(select.choices.len(), 1);
+    assert!(h.tasker.render_to_string().contains("Load filter: wkdn"));
+    h.key(KeyCode::Enter);
+    assert_eq!(h.tasker.tasklist.tasks, vec![done]);
+
+    // Backspacing widens the choices again.
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('l'));
+    h.type_text("fx").key(KeyCode::Backspace);
+    assert_eq!(h.tasker.quick_select.as_ref().unwrap().choices.len(), 10);
+    h.key(KeyCode::Enter);
+    assert_eq!(h.tasker.tasklist.tasks, vec![todo, done]);
+}
+
+#[test]
+fn test_expand_link_description() {
+    let mut h = Harness::new();
+    h.create_task("First");
+    h.create_task("Second");
+    h.key(KeyCode::Char('e')).type_text("Details of second");
+    h.key(KeyCode::Enter);
+
+    h.key(KeyCode::Up).key(KeyCode::Enter);
+    h.key(KeyCode::Char('l'))
+        .type_text("Second")
+        .key(KeyCode::Enter);
+    assert!(!h.tasker.render_to_string().contains("Details of second"));
+
+    h.key(KeyCode::Char('x'));
+    assert!(h.tasker.render_to_string().contains("Details of second"));
+    h.key(KeyCode::Char('x'));
+    assert!(!h.tasker.render_to_string().contains("Details of second"));
+}
+
+#[test]
+fn test_export_dot_file() {
+    let mut h = Harness::new();
+    h.create_task("a");
+    let path = std::env::temp_dir().join(format!("tasker-{}.dot", std::process::id()));
+
+    h.key(KeyCode::Char('X'));
+    h.tasker.quick_input.as_mut().unwrap().text = path.display().to_string();
+    h.key(KeyCode::Enter);
+    let dot = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(dot.contains("[label=\"a\"]"));
+    assert!(h
+        .tasker
+        .notification
+        .as_deref()
+        .unwrap()
+        .starts_with("Exported"));
+
+    h.key(KeyCode::Char('X'));
+    h.tasker.quick_input.as_mut().unwrap().text = path.join("missing").display().to_string();
+    h.key(KeyCode::Enter);
+    assert!(h
+        .tasker
+        .notification
+        .as_deref()
+        .unwrap()
+        .starts_with("Export failed"));
+}
+
+#[test]
+fn test_reminder() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.reminder_sound = Some("missing.wav".into());
+    let a = h.create_task("Call back");
+    h.create_task("Other");
+
+    h.key(KeyCode::Up).key(KeyCode::Char('r'));
+    h.type_text("30").key(KeyCode::Enter);
+    assert!(h.tasker.render_to_string().contains("Reminder in 30 min"));
+
+    clock.advance(Duration::from_secs(60 * 29));
+    h.tasker.update();
+    assert_eq!(h.tasker.notification, None);
+    assert!(h.tasker.render_to_string().contains("Reminder in 1 min"));
+
+    clock.advance(Duration::from_secs(60));
+    h.tasker.update();
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Reminder: Call back")
+    );
+    assert_eq!(h.tasker.data.store.get_task(a).reminder, None);
+}
+
+#[test]
+fn test_reminder_at_time_of_day() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    // 12:00 local time, two hours east of UTC.
+    let noon = UNIX_EPOCH + Duration::from_secs(60 * 60 * (24 * 100 + 10));
+    clock.set_wall(noon);
+    clock.set_utc_offset(2 * 60 * 60);
+    let a = h.create_task("Call back");
+    let three_pm = noon + Duration::from_secs(3 * 60 * 60);
+
+    for text in ["15:00", "3pm", "3:00 PM"] {
+        h.key(KeyCode::Char('r')).type_text(text);
+        let feedback = h.tasker.quick_input.as_ref().unwrap().feedback.clone();
+        assert_eq!(feedback, Some(Ok("in 180 min".into())));
+        h.key(KeyCode::Enter);
+        assert_eq!(h.tasker.data.store.get_task(a).reminder, Some(three_pm));
+    }
+
+    // Times already past today are tomorrow.
+    h.key(KeyCode::Char('r'))
+        .type_text("9am")
+        .key(KeyCode::Enter);
+    let reminder = h.tasker.data.store.get_task(a).reminder;
+    assert_eq!(reminder, Some(noon + Duration::from_secs(21 * 60 * 60)));
+
+    h.key(KeyCode::Char('r')).type_text("25:00");
+    let feedback = h.tasker.quick_input.as_ref().unwrap().feedback.clone();
+    assert_eq!(feedback, Some(Err("not a time".into())));
+    h.key(KeyCode::Enter);
+    assert!(h.tasker.quick_input.is_some());
+    h.key(KeyCode::Esc);
+
+    h.key(KeyCode::Char('r')).type_text("0").key(KeyCode::Enter);
+    assert_eq!(h.tasker.data.store.get_task(a).reminder, None);
+}
+
+#[test]
+fn test_reminders_due_together() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.reminder_sound = Some("missing.wav".into());
+    let a = h.create_task("Call back");
+    let b = h.create_task("Water plants");
+    let due = clock.wall_now() + Duration::from_secs(60);
+    h.tasker.data.store.get_task_mut(a).reminder = Some(due);
+    h.tasker.data.store.get_task_mut(b).reminder = Some(due);
+
+    clock.advance(Duration::from_secs(60));
+    h.tasker.update();
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Reminders: Call back, Water plants")
+    );
+}
+
+#[test]
+fn test_toggle_full_task_view() {
+    let mut h = Harness::new();
+    h.create_task("First");
+    h.create_task("Second");
+    h.key(KeyCode::Up).key(KeyCode::Enter);
+    h.key(KeyCode::Char('l'))
+        .type_text("Second")
+        .key(KeyCode::Enter);
+    assert!(h.tasker.render_to_string().contains("Linked tasks"));
+
+    h.key(KeyCode::Char('v'));
+    assert!(!h.tasker.render_to_string().contains("Linked tasks"));
+
+    // Following a link keeps the single-column layout.
+    h.key(KeyCode::Enter);
+    let screen = h.tasker.render_to_string();
+    assert!(screen.contains(" Second "));
+    assert!(!screen.contains("Linked tasks"));
+}
+
+#[test]
+fn test_quick_select_hints() {
+    let mut h = Harness::new();
+    h.create_task("a");
+
+    h.key(KeyCode::Char('c'));
+    let screen = h.tasker.render_to_string();
+    let last = screen.lines().last().unwrap();
+    assert!(last.contains("[r] Red"));
+    assert!(last.contains("(Enter select, Esc cancel)"));
+    h.key(KeyCode::Esc);
+
+    // Vertical menus keep the hint on the bottom line.
+    h.key(KeyCode::Char('f'));
+    let screen = h.tasker.render_to_string();
+    let last = screen.lines().last().unwrap();
+    assert!(!last.contains("[t] Title"));
+    assert!(last.contains("(Enter select, Esc cancel)"));
+}
+
+#[test]
+fn test_clear_done_tasks() {
+    let mut h = Harness::new();
+    let a = h.create_task("a");
+    h.create_task("b");
+    h.create_task("c");
+    h.key(KeyCode::Char(' '))
+        .key(KeyCode::Up)
+        .key(KeyCode::Char(' '));
+
+    h.key(KeyCode::Char('D')).key(KeyCode::Char('n'));
+    assert_eq!(h.tasker.data.store.len(), 3);
+
+    h.key(KeyCode::Char('D')).key(KeyCode::Char('y'));
+    assert_eq!(h.tasker.tasklist.tasks, vec![a]);
+    assert_eq!(h.tasker.data.store.trash().len(), 2);
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("Moved 2 done tasks to the trash")
+    );
+
+    h.key(KeyCode::Char('D'));
+    assert!(h.tasker.quick_select.is_none());
+    assert_eq!(
+        h.tasker.notification.as_deref(),
+        Some("No done tasks to clear")
+    );
+}
+
+#[test]
+fn test_replacing_a_timer_clears_it_first() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    h.tasker.data.config.credit_partial_work = true;
+    let id = h.create_task("Focus");
+
+    // A short timer is replaced at once, crediting its time.
+    h.key(KeyCode::Char('p')).key(KeyCode::Char('p'));
+    clock.advance(Duration::from_secs(60 * 2));
+    h.key(KeyCode::Char('p')).key(KeyCode::Char('b'));
+    assert_eq!(h.tasker.timer.as_ref().unwrap().title, "BREAK");
+    let spent = h.tasker.data.store.get_task(id).time_spent;
+    assert_eq!(spent, Duration::from_secs(60 * 2));
+
+    // A long one asks first, from the task view too.
+    h.key(KeyCode::Enter).key(KeyCode::Char('p'));
+    clock.advance(Duration::from_secs(60 * 10));
+    h.key(KeyCode::Char('p'));
+    assert_eq!(
+        h.tasker.quick_select.as_ref().unwrap().title,
+        "Stop after 10 min of focus?"
+    );
+    h.key(KeyCode::Char('n'));
+    assert_eq!(h.tasker.timer.as_ref().unwrap().title, "WORK");
+    assert_eq!(
+        h.tasker.data.store.get_task(id).time_spent,
+        Duration::from_secs(60 * 2)
+    );
+
+    h.key(KeyCode::Char('p'));
+    clock.advance(Duration::from_secs(30));
+    h.key(KeyCode::Char('y'));
+    let timer = h.tasker.timer.as_ref().unwrap();
+    assert_eq!(timer.elapsed(clock.now()), Duration::ZERO);
+    assert_eq!(
+        h.tasker.data.store.get_task(id).time_spent,
+        Duration::from_secs(60 * 12 + 30)
+    );
+}
+
+#[test]
+fn test_capture_in_focus_mode() {
+    let mut h = Harness::new();
+    h.create_task("Deep work");
+
+    h.key(KeyCode::Char('z'));
+    h.key_with(KeyCode::Char('n'), KeyModifiers::CONTROL)
+        .type_text("Idea");
+    assert!(h.tasker.focus.is_some());
+    let screen = h.tasker.render_to_string();
+    assert!(screen.lines().last().unwrap().contains("Idea"));
+
+    h.key(KeyCode::Enter);
+    assert!(h.tasker.focus.is_some());
+    let screen = h.tasker.render_to_string();
+    assert!(screen.lines().last().unwrap().contains("Captured 'Idea'"));
+}
+
+#[test]
+fn test_completions_cache_follows_store() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    let id = h.create_task("Task");
+
+    let now = clock.wall_now();
+    assert_eq!(h.tasker.data.completions_per_day(now).last(), Some(&0));
+    h.tasker
+        .data
+        .store
+        .get_task_mut(id)
+        .set_status(Status::Done, now);
+    assert_eq!(h.tasker.data.completions_per_day(now).last(), Some(&1));
+
+    clock.advance(Duration::from_secs(60 * 60 * 24));
+    let counts = h.tasker.data.completions_per_day(clock.wall_now());
+    assert_eq!(counts[counts.len() - 2..], [1, 0]);
+}
+
+#[test]
+fn test_load_without_saved_filters() {
+    let mut h = Harness::new();
+    h.create_task("First");
+    let second = h.create_task("Second");
+
+    h.key(KeyCode::Char('f')).key(KeyCode::Char('l'));
+    assert_eq!(h.tasker.notification.as_deref(), Some("No saved filters"));
+    assert!(h.tasker.quick_select.is_none());
+    assert_eq!(h.tasker.tasklist.selection(), Some(second));
+}
+
+#[test]
+fn test_trash_selection_stays_in_range() {
+    let mut h = Harness::new();
+    h.create_task("Keep");
+    h.create_task("First");
+    h.key(KeyCode::Delete);
+    h.create_task("Second");
+    h.key(KeyCode::Delete);
+
+    h.key(KeyCode::Char('t'))
+        .key(KeyCode::Down)
+        .key(KeyCode::Char('x'));
+    assert_eq!(h.tasker.data.store.trash().len(), 1);
+    h.key(KeyCode::Char('r'));
+    assert!(h.tasker.data.store.trash().is_empty());
+    assert_eq!(h.tasker.data.store.find_by_title("First").len(), 1);
+
+    // Nothing left to act on.
+    h.key(KeyCode::Char('c'))
+        .key(KeyCode::Char('r'))
+        .key(KeyCode::Char('x'));
+    assert_eq!(h.tasker.data.store.len(), 2);
+}
+
+#[test]
+fn test_completion_time_follows_clock() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    let id = h.create_task("Task");
+
+    h.key(KeyCode::Char(' '));
+    let completed = h.tasker.data.store.get_task(id).completed_at;
+    assert_eq!(completed, Some(clock.wall_now()));
+    assert!(h.tasker.render_to_string().contains("Completed just now"));
+
+    clock.advance(Duration::from_secs(60 * 60 * 3));
+    assert!(h
+        .tasker
+        .render_to_string()
+        .contains("Completed 3 hours ago"));
+}
+
+#[test]
+fn test_completions_split_at_local_midnight() {
+    let mut h = Harness::new();
+    let clock = Rc::new(MockClock::new());
+    h.tasker.data.clock = clock.clone();
+    clock.set_utc_offset(60 * 60);
+    let id = h.create_task("Task");
+
+    // 23:30 local time, 22:30 UTC.
+    clock.set_wall(UNIX_EPOCH + Duration::from_secs(60 * 60 * 24 * 100 - 60 * 90));
+    h.key(KeyCode::Char(' '));
+    assert_eq!(h.tasker.data.store.get_task(id).status, Status::Done);
+
+    // An hour later it's a new local day, but the same UTC one.
+    clock.set_wall(UNIX_EPOCH + Duration::from_secs(60 * 60 * 24 * 100 - 60 * 30));
+    let counts = h.tasker.data.completions_per_day(clock.wall_now());
+    assert_eq!(counts[counts.len() - 2..], [1, 0]);
+}